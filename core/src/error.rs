@@ -0,0 +1,298 @@
+//!
+//! Errors of the register map library
+//! Shared by the runtime (i.e. FlatRegmap and json loader) and the generator, thus it doesn't
+//! depend on std.
+//!
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use thiserror::Error;
+
+use crate::model::{DefaultVal, Owner, ReadAccess, Reserved, WriteAccess};
+use crate::span::SrcCtx;
+
+/// Global parsing error
+/// Describe potential register error and incompatible options
+#[derive(Error, Debug, Clone)]
+pub enum RegmapError {
+    #[error("Couldn't generate Regmap from empty RegmapOpt list")]
+    NoEntry,
+    #[error("Error: Couldn't merge register map with != word_size_b")]
+    WordSize,
+    #[error(
+        "Field definition crossed word-boundary:[ Word width (bits): {word_b}, Field [offset {field_offset}, width {field_b}]]\n{ctx}"
+    )]
+    WordBoundary {
+        word_b: usize,
+        field_offset: usize,
+        field_b: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Default defined at both level (i.e. register & field):\n{ctx}")]
+    DfltOverride { ctx: SrcCtx },
+    #[error("Expect Param or Cst [get: {dflt:?}]:\n{ctx}")]
+    DfltInvalid { dflt: DefaultVal, ctx: SrcCtx },
+    #[error("Default value 0x{value:x} doesn't fit in {width} bits:\n{ctx}")]
+    DfltOverflow {
+        value: usize,
+        width: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Incompatible Access right for {owner:?} [rd: {rd:?}, wr: {wr:?}]:\n{ctx}")]
+    Access {
+        owner: Owner,
+        rd: ReadAccess,
+        wr: WriteAccess,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Invalid offset: [Minimal offset: 0x{min_offset:x}, Requested offset: 0x{request_offset:x}]\n{ctx}"
+    )]
+    Offset {
+        min_offset: usize,
+        request_offset: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Invalid range: Range must be a power of two.\n{ctx}")]
+    RangePow2 { range: usize, ctx: SrcCtx },
+    #[error(
+        "Invalid range: [Real range: 0x{real_range:x}, Requested range: 0x{request_range:x}]\n{ctx}"
+    )]
+    Range {
+        request_range: usize,
+        real_range: usize,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Invalid Range: [Range: 0x{range:x}, Offset: 0x{offset:x}] must not overlaps to enable clever addr filtering.\n{ctx}"
+    )]
+    OffsetRange {
+        offset: usize,
+        range: usize,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Invalid alignment:[Word alignment: 0x{word_align}, Requested alignment: 0x{request_align}]\n{ctx}"
+    )]
+    ByteAlign {
+        word_align: usize,
+        request_align: usize,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Field `{field}` [offset {field_offset}, width {field_b}] overlaps field `{other}` [offset {other_offset}, width {other_b}]:\n{ctx}"
+    )]
+    FieldOverlap {
+        field: String,
+        field_offset: usize,
+        field_b: usize,
+        other: String,
+        other_offset: usize,
+        other_b: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Duplicate {kind} name `{name}` after expansion:\n{ctx}")]
+    DuplicateName {
+        kind: String,
+        name: String,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Locked {kind} `{name}` would move: [Locked offset: 0x{locked:x}, Requested offset: 0x{request:x}]\n{ctx}"
+    )]
+    LockedOffset {
+        kind: String,
+        name: String,
+        locked: usize,
+        request: usize,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Address of {kind} `{name}` [offset: 0x{offset:x}] overlaps `{other}` [offset: 0x{other_offset:x}]:\n{ctx}"
+    )]
+    AddressOverlap {
+        kind: String,
+        name: String,
+        offset: usize,
+        other: String,
+        other_offset: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Lock file `{file}` is out of date [added: {added:?}, removed: {removed:?}]")]
+    LockOutdated {
+        file: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    #[error("Unsupported lock version {found} in `{file}` (supported: {supported})")]
+    LockVersion {
+        file: String,
+        found: usize,
+        supported: usize,
+    },
+    #[error("Cyclic block instantiation [{}]:\n{ctx}", chain.join(" -> "))]
+    BlockCycle { chain: Vec<String>, ctx: SrcCtx },
+    #[error("Couldn't include `{file}` [{}]: {msg}\n{ctx}", chain.join(" -> "))]
+    Include {
+        file: String,
+        chain: Vec<String>,
+        msg: String,
+        ctx: SrcCtx,
+    },
+    #[error("Unknown variant `{variant}`:\n{ctx}")]
+    UnknownVariant { variant: String, ctx: SrcCtx },
+    #[error("Invalid predicate `{cond}`: {msg}\n{ctx}")]
+    Predicate {
+        cond: String,
+        msg: String,
+        ctx: SrcCtx,
+    },
+    #[error("Invalid {policy} reservation: {msg}\n{ctx}")]
+    Reserved {
+        policy: Reserved,
+        msg: String,
+        ctx: SrcCtx,
+    },
+    #[error("Missing `{key}` property:\n{ctx}")]
+    MissingKey { key: String, ctx: SrcCtx },
+    #[error("Unknown `{key}` property:\n{ctx}")]
+    UnknownKey { key: String, ctx: SrcCtx },
+    #[error("Invalid instance of template `{template}`: {msg}\n{ctx}")]
+    Template {
+        template: String,
+        msg: String,
+        ctx: SrcCtx,
+    },
+    #[error(
+        "Parameter `{param}` = {value} isn't a valid width for a field of {max_b} bits at most\n{ctx}"
+    )]
+    SizeParam {
+        param: String,
+        value: usize,
+        max_b: usize,
+        ctx: SrcCtx,
+    },
+    #[error("Invalid expression `{expr}`: {msg}\n{ctx}")]
+    Expr {
+        expr: String,
+        msg: String,
+        ctx: SrcCtx,
+    },
+    #[error("Couldn't read `{file}`: {msg}")]
+    Io { file: String, msg: String },
+    #[error("Invalid toml syntax in `{file}` [line: {line}, column: {column}]:\n  => {msg}")]
+    TomlSyntax {
+        file: String,
+        line: usize,
+        column: usize,
+        msg: String,
+    },
+    #[error("Invalid json syntax in `{file}` [line: {line}, column: {column}]:\n  => {msg}")]
+    JsonSyntax {
+        file: String,
+        line: usize,
+        column: usize,
+        msg: String,
+    },
+    #[error("Unsupported json schema version {found} in `{file}` (supported: {supported})")]
+    JsonVersion {
+        file: String,
+        found: u32,
+        supported: u32,
+    },
+    #[error("Register `{name}` isn't available in the register map")]
+    UnknownRegister { name: String },
+    #[error("Field `{field}` isn't available in register `{register}`")]
+    UnknownField { register: String, field: String },
+    #[error("Register `{register}` is reserved [{policy}]")]
+    ReservedRegister { register: String, policy: Reserved },
+    #[error("Field `{field}` of register `{register}` is reserved [{policy}]")]
+    ReservedField {
+        register: String,
+        field: String,
+        policy: Reserved,
+    },
+    #[error("Value 0x{value:x} overflows field `{field}` [width (bits): {size_b}]")]
+    ValueOverflow {
+        field: String,
+        value: u32,
+        size_b: usize,
+    },
+}
+
+impl RegmapError {
+    /// Name of the error kind
+    /// Used in machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NoEntry => "NoEntry",
+            Self::WordSize => "WordSize",
+            Self::WordBoundary { .. } => "WordBoundary",
+            Self::DfltOverride { .. } => "DfltOverride",
+            Self::DfltInvalid { .. } => "DfltInvalid",
+            Self::DfltOverflow { .. } => "DfltOverflow",
+            Self::Access { .. } => "Access",
+            Self::Offset { .. } => "Offset",
+            Self::RangePow2 { .. } => "RangePow2",
+            Self::Range { .. } => "Range",
+            Self::OffsetRange { .. } => "OffsetRange",
+            Self::ByteAlign { .. } => "ByteAlign",
+            Self::FieldOverlap { .. } => "FieldOverlap",
+            Self::DuplicateName { .. } => "DuplicateName",
+            Self::LockedOffset { .. } => "LockedOffset",
+            Self::AddressOverlap { .. } => "AddressOverlap",
+            Self::LockOutdated { .. } => "LockOutdated",
+            Self::LockVersion { .. } => "LockVersion",
+            Self::BlockCycle { .. } => "BlockCycle",
+            Self::Include { .. } => "Include",
+            Self::Template { .. } => "Template",
+            Self::Reserved { .. } => "Reserved",
+            Self::MissingKey { .. } => "MissingKey",
+            Self::UnknownKey { .. } => "UnknownKey",
+            Self::UnknownVariant { .. } => "UnknownVariant",
+            Self::Predicate { .. } => "Predicate",
+            Self::SizeParam { .. } => "SizeParam",
+            Self::Expr { .. } => "Expr",
+            Self::Io { .. } => "Io",
+            Self::TomlSyntax { .. } => "TomlSyntax",
+            Self::JsonSyntax { .. } => "JsonSyntax",
+            Self::JsonVersion { .. } => "JsonVersion",
+            Self::UnknownRegister { .. } => "UnknownRegister",
+            Self::UnknownField { .. } => "UnknownField",
+            Self::ReservedRegister { .. } => "ReservedRegister",
+            Self::ReservedField { .. } => "ReservedField",
+            Self::ValueOverflow { .. } => "ValueOverflow",
+        }
+    }
+
+    /// Source context of the error, if any
+    pub fn ctx(&self) -> Option<&SrcCtx> {
+        match self {
+            Self::WordBoundary { ctx, .. }
+            | Self::DfltOverride { ctx }
+            | Self::DfltInvalid { ctx, .. }
+            | Self::DfltOverflow { ctx, .. }
+            | Self::Access { ctx, .. }
+            | Self::Offset { ctx, .. }
+            | Self::RangePow2 { ctx, .. }
+            | Self::Range { ctx, .. }
+            | Self::OffsetRange { ctx, .. }
+            | Self::ByteAlign { ctx, .. }
+            | Self::FieldOverlap { ctx, .. }
+            | Self::DuplicateName { ctx, .. }
+            | Self::LockedOffset { ctx, .. }
+            | Self::AddressOverlap { ctx, .. }
+            | Self::BlockCycle { ctx, .. }
+            | Self::Include { ctx, .. }
+            | Self::Template { ctx, .. }
+            | Self::Reserved { ctx, .. }
+            | Self::MissingKey { ctx, .. }
+            | Self::UnknownKey { ctx, .. }
+            | Self::UnknownVariant { ctx, .. }
+            | Self::Predicate { ctx, .. }
+            | Self::SizeParam { ctx, .. }
+            | Self::Expr { ctx, .. } => Some(ctx),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//!
+//! Register map model, parsing and elaboration
+//! Shared by `hw_regmap` (i.e. generators and runtime) and `hw_regmap_macros` (i.e. compile-time
+//! embedding). Everything is re-exported by `hw_regmap`, thus users shouldn't depend on it
+//! directly.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod error;
+pub mod model;
+pub mod span;
+
+#[cfg(feature = "elab")]
+pub mod regmap;
+
+pub use error::RegmapError;
+
+#[cfg(feature = "elab")]
+pub use regmap::Regmap;
+#[cfg(feature = "elab")]
+pub use regmap::parser::RegmapOpt;
@@ -0,0 +1,346 @@
+//!
+//! Register map model shared by the runtime and the generator
+//! Access rights, default values and the json interchange format of an elaborated register map.
+//! Only depends on `alloc`, thus firmware could embed a regmap without the generator stack.
+//!
+//! Json interchange format: internal structures of the generator are free to change, this model
+//! is the contract with the downstream tools (i.e. `json` generator output, `Regmap::from_json`
+//! and `FlatRegmap::from_json_str` input).
+//! Stability: any breaking change of the model (i.e. removed or renamed key, changed meaning)
+//! bumps `SCHEMA_VERSION`. New optional keys are backward compatible and keep the version, thus
+//! readers ignore unknown keys.
+//!
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::RegmapError;
+
+// NB: Owner, ReadAccess, WriteAccess are split to ease the Serde
+//    and have a clear naming in toml without manual implementation of the serde traits
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "elab", derive(schemars::JsonSchema))]
+pub enum Owner {
+    User,
+    Kernel,
+    Parameter,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "elab", derive(schemars::JsonSchema))]
+pub enum ReadAccess {
+    None,
+    Read,
+    ReadNotify,
+}
+
+impl ReadAccess {
+    pub fn is_read(&self) -> bool {
+        match self {
+            Self::None => false,
+            Self::Read | Self::ReadNotify => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "elab", derive(schemars::JsonSchema))]
+pub enum WriteAccess {
+    None,
+    Write,
+    WriteNotify,
+}
+
+impl WriteAccess {
+    pub fn is_write(&self) -> bool {
+        match self {
+            Self::None => false,
+            Self::Write | Self::WriteNotify => true,
+        }
+    }
+}
+
+/// Semantics of reserved registers, bit ranges and address ranges
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[cfg_attr(feature = "elab", derive(schemars::JsonSchema))]
+pub enum Reserved {
+    /// Read as 0, writes are ignored
+    ReadAsZero,
+    /// Writes are ignored, read as the default value
+    WriteIgnored,
+    /// Stored as written, the software must write back the read value
+    PreserveOnWrite,
+}
+
+impl Reserved {
+    /// Owner and access rights implied by the policy
+    pub fn access(&self) -> (Owner, ReadAccess, WriteAccess) {
+        match self {
+            Self::ReadAsZero | Self::WriteIgnored => {
+                (Owner::User, ReadAccess::Read, WriteAccess::None)
+            }
+            Self::PreserveOnWrite => (Owner::User, ReadAccess::Read, WriteAccess::Write),
+        }
+    }
+}
+
+impl fmt::Display for Reserved {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// Bit mask of a field with `width` bits, not shifted
+/// NB: Handle empty and full word fields without shift overflow
+pub fn field_mask(width: usize) -> u64 {
+    match width {
+        0 => 0,
+        w if w >= u64::BITS as usize => u64::MAX,
+        w => (1 << w) - 1,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub enum DefaultVal {
+    /// Hardcoded value
+    Cst(usize),
+    /// Value extract from a Rtl parameters
+    Param(String),
+    /// Value construct from a list of parameters
+    ///  * name field: contains the list of used parameters
+    ///  * name_val: contains pair of field_name, value
+    ParamsField {
+        params: Vec<String>,
+        name_val: Vec<(String, String)>,
+    },
+}
+
+impl DefaultVal {
+    pub fn params_list(&self) -> Vec<String> {
+        match self {
+            Self::Cst(_) => vec![],
+            Self::Param(str) => vec![str.clone()],
+            Self::ParamsField { params, .. } => params.clone(),
+        }
+    }
+}
+
+/// Version of the json model
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Versioned document, i.e. root of the json file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonDoc {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub regmap: JsonRegmap,
+}
+
+/// Version header of a document
+/// NB: Parsed first, thus a document of another version is rejected whatever its content
+#[derive(Deserialize)]
+struct JsonVersion {
+    schema_version: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRegmap {
+    pub module_name: String,
+    pub description: String,
+    pub word_size_b: usize,
+    pub offset: usize,
+    pub range: usize,
+    /// Sorted list of the SystemVerilog packages imported by the RTL
+    pub ext_pkg: Vec<String>,
+    /// Values of the parameters used as default value (only the valued ones)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub param: BTreeMap<String, usize>,
+    /// Sections in address order
+    pub section: Vec<JsonSection>,
+    /// Child regmaps instantiated in this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub block: Vec<JsonBlock>,
+    /// Fused definitions (empty for a single definition)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slice: Vec<JsonSlice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSection {
+    pub name: String,
+    pub description: String,
+    /// Absolute address of the section
+    pub offset: usize,
+    pub bytes_align: usize,
+    pub range: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved: Option<Reserved>,
+    pub register: Vec<JsonRegister>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonRegister {
+    pub name: String,
+    pub description: String,
+    pub owner: Owner,
+    pub read_access: ReadAccess,
+    pub write_access: WriteAccess,
+    /// Absolute address of the register
+    pub offset: usize,
+    /// Alignment of the offset in bytes, added without version bump thus optional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes_align: Option<usize>,
+    pub default: JsonDefault,
+    /// Concrete default value, omitted if it relies on a parameter without value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved: Option<Reserved>,
+    /// Fields sorted by bit offset, empty for a register without field
+    #[serde(default)]
+    pub field: Vec<JsonField>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonField {
+    pub name: String,
+    pub description: String,
+    /// Width in bits, maximum width of a parameterized field
+    pub size_b: usize,
+    /// RTL parameter giving the actual width
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_param: Option<String>,
+    /// Concrete width of a parameterized field, omitted without parameter value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    pub offset_b: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<JsonDefault>,
+    /// Concrete default value, omitted if it relies on a parameter without value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved: Option<Reserved>,
+}
+
+/// Default value, tagged with its kind
+/// e.g. `{"kind": "const", "value": 0}` or `{"kind": "param", "name": "VERSION"}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JsonDefault {
+    Const {
+        value: usize,
+    },
+    Param {
+        name: String,
+    },
+    /// Register value built from the defaults of its fields
+    ParamsField {
+        params: Vec<String>,
+        field: Vec<JsonFieldDefault>,
+    },
+}
+
+/// Default of a field in a `params_field` default
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonFieldDefault {
+    pub name: String,
+    /// SystemVerilog expression of the value (i.e. parameter name or literal)
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonBlock {
+    pub name: String,
+    pub description: String,
+    /// Definition file of the child regmap, as written in the parent
+    pub file: String,
+    pub offset: usize,
+    pub range: usize,
+    pub regmap: JsonRegmap,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonSlice {
+    pub module_name: String,
+    pub description: String,
+    pub offset: usize,
+    pub range: usize,
+    /// Names of the sections and blocks defined in this slice
+    pub section: Vec<String>,
+    pub block: Vec<String>,
+}
+
+impl JsonDoc {
+    /// Parse a json interchange document, only the supported version is accepted
+    /// NB: file is only used to give context in error message
+    pub fn from_json_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        let syntax = |err: serde_json::Error| RegmapError::JsonSyntax {
+            file: file.to_string(),
+            line: err.line(),
+            column: err.column(),
+            msg: err.to_string(),
+        };
+        let version = serde_json::from_str::<JsonVersion>(content).map_err(syntax)?;
+        if version.schema_version != SCHEMA_VERSION {
+            return Err(RegmapError::JsonVersion {
+                file: file.to_string(),
+                found: version.schema_version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        serde_json::from_str(content).map_err(syntax)
+    }
+
+    /// Read a json interchange file
+    #[cfg(feature = "std")]
+    pub fn read_from(file: &str) -> Result<Self, RegmapError> {
+        let content = std::fs::read_to_string(file).map_err(|err| RegmapError::Io {
+            file: file.to_string(),
+            msg: err.to_string(),
+        })?;
+        Self::from_json_str(file, &content)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks() {
+        assert_eq!(field_mask(0), 0);
+        assert_eq!(field_mask(4), 0xf);
+        assert_eq!(field_mask(64), u64::MAX);
+        assert_eq!(field_mask(65), u64::MAX);
+    }
+
+    #[test]
+    fn version_checked_first() {
+        // NB: Newer model without the keys of the current one
+        let content = r#"{ "schema_version": 2, "name": "renamed" }"#;
+        match JsonDoc::from_json_str("test.json", content) {
+            Err(RegmapError::JsonVersion {
+                found, supported, ..
+            }) => assert_eq!((found, supported), (2, SCHEMA_VERSION)),
+            other => panic!("Expect a JsonVersion error, get {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_key_with_supported_version() {
+        let content = r#"{ "schema_version": 1, "name": "renamed" }"#;
+        assert!(matches!(
+            JsonDoc::from_json_str("test.json", content),
+            Err(RegmapError::JsonSyntax { .. })
+        ));
+        assert!(matches!(
+            JsonDoc::from_json_str("test.json", "{}"),
+            Err(RegmapError::JsonSyntax { .. })
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+//!
+//! Address allocation of the elements inside their parent
+//! (i.e. registers inside a section and sections inside a regmap)
+//!
+//! With the sequential strategy, elements are placed one after the other in definition order.
+//! With the fit strategies, fixed elements (i.e. locked or explicit offset) are reserved first, then
+//! the others are placed in the free holes.
+//!
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::diag::Diagnostics;
+use super::span::SrcCtx;
+use super::{RegmapError, align_on};
+
+/// Allocation strategy of the elements without explicit offset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Monotonic cursor: each element is placed after the previous one, explicit offsets must be
+    /// given in increasing order
+    #[default]
+    Sequential,
+    /// Lowest hole large enough
+    FirstFit,
+    /// Smallest hole large enough, lowest one on tie
+    BestFit,
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sequential" => Ok(Self::Sequential),
+            "first_fit" => Ok(Self::FirstFit),
+            "best_fit" => Ok(Self::BestFit),
+            _ => Err(format!(
+                "unknown strategy `{s}` [available: sequential, first_fit, best_fit]"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Strategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Sequential => "sequential",
+            Self::FirstFit => "first_fit",
+            Self::BestFit => "best_fit",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Set of used address ranges
+#[derive(Debug, Clone, Default)]
+pub struct Allocator {
+    /// Used ranges with their owner name, sorted by start address
+    used: Vec<(Range<usize>, String)>,
+}
+
+impl Allocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Owner and range of the first used range that overlaps `range`
+    pub fn overlap(&self, range: &Range<usize>) -> Option<(&Range<usize>, &str)> {
+        self.used
+            .iter()
+            .find(|(used, _)| used.start < range.end && range.start < used.end)
+            .map(|(used, name)| (used, name.as_str()))
+    }
+
+    /// End of the highest used range
+    pub fn end(&self) -> usize {
+        self.used
+            .iter()
+            .map(|(used, _)| used.end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Mark a range as used
+    pub fn reserve(&mut self, range: Range<usize>, owner: &str) {
+        let pos = self
+            .used
+            .partition_point(|(used, _)| used.start <= range.start);
+        self.used.insert(pos, (range, owner.to_string()));
+    }
+
+    /// Address of a new element with the given strategy
+    /// NB: Sequential isn't a fit strategy, it falls back to first fit
+    pub fn place(&self, strategy: Strategy, size: usize, align: usize, start: usize) -> usize {
+        match strategy {
+            Strategy::Sequential | Strategy::FirstFit => self.first_fit(size, align, start),
+            Strategy::BestFit => self.best_fit(size, align, start),
+        }
+    }
+
+    /// Aligned address above `start` in the smallest hole with `size` free bytes
+    /// The open-ended hole after the last used range is only used if nothing else fits
+    pub fn best_fit(&self, size: usize, align: usize, start: usize) -> usize {
+        let mut best: Option<(usize, usize)> = None;
+        let mut hole_start = start;
+        for (used, _) in self.used.iter() {
+            if used.end <= hole_start {
+                continue;
+            }
+            let candidate = align_on(align, hole_start);
+            if candidate + size <= used.start {
+                let hole_size = used.start - hole_start;
+                if best.is_none_or(|(_, best_size)| hole_size < best_size) {
+                    best = Some((candidate, hole_size));
+                }
+            }
+            hole_start = hole_start.max(used.end);
+        }
+        match best {
+            Some((candidate, _)) => candidate,
+            None => align_on(align, hole_start),
+        }
+    }
+
+    /// Lowest aligned address above `start` with `size` free bytes
+    pub fn first_fit(&self, size: usize, align: usize, start: usize) -> usize {
+        let mut candidate = align_on(align, start);
+        for (used, _) in self.used.iter() {
+            if used.end <= candidate {
+                continue;
+            }
+            if candidate + size <= used.start {
+                break;
+            }
+            candidate = align_on(align, used.end);
+        }
+        candidate
+    }
+
+    /// Mark a fixed range as used
+    /// Overlap with an already used range is reported
+    pub fn claim(
+        &mut self,
+        kind: &str,
+        name: &str,
+        range: Range<usize>,
+        ctx: &SrcCtx,
+        diag: &mut Diagnostics,
+    ) {
+        if let Some((used, other)) = self.overlap(&range) {
+            diag.push(RegmapError::AddressOverlap {
+                kind: kind.to_string(),
+                name: name.to_string(),
+                offset: range.start,
+                other: other.to_string(),
+                other_offset: used.start,
+                ctx: ctx.with_hint(format!(
+                    "`{other}` already uses [0x{:x}, 0x{:x})",
+                    used.start, used.end
+                )),
+            });
+        }
+        self.reserve(range, name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Used ranges: [0x0, 0x8), [0x10, 0x14) and [0x20, 0x30)
+    /// -> Holes: [0x8, 0x10) of 8 bytes, [0x14, 0x20) of 12 bytes and the open-ended one at 0x30
+    fn allocator() -> Allocator {
+        let mut alloc = Allocator::new();
+        alloc.reserve(0x20..0x30, "c");
+        alloc.reserve(0x0..0x8, "a");
+        alloc.reserve(0x10..0x14, "b");
+        alloc
+    }
+
+    #[test]
+    fn first_fit_lowest_hole() {
+        let alloc = allocator();
+        assert_eq!(alloc.first_fit(4, 4, 0), 0x8);
+        assert_eq!(alloc.first_fit(8, 4, 0), 0x8);
+        // NB: Too large for the first hole
+        assert_eq!(alloc.first_fit(12, 4, 0), 0x14);
+        // NB: Holes below start are skipped
+        assert_eq!(alloc.first_fit(4, 4, 0x10), 0x14);
+    }
+
+    #[test]
+    fn best_fit_smallest_hole() {
+        let alloc = allocator();
+        assert_eq!(alloc.best_fit(4, 4, 0), 0x8);
+        assert_eq!(alloc.best_fit(12, 4, 0), 0x14);
+        // NB: Both holes fit, the smallest wins even if it isn't the lowest
+        let mut alloc = Allocator::new();
+        alloc.reserve(0x0..0x4, "a");
+        alloc.reserve(0x20..0x24, "b");
+        alloc.reserve(0x28..0x30, "c");
+        assert_eq!(alloc.best_fit(4, 4, 0), 0x24);
+        assert_eq!(alloc.first_fit(4, 4, 0), 0x4);
+    }
+
+    #[test]
+    fn alignment_inside_holes() {
+        let alloc = allocator();
+        // NB: 0x8 isn't 16-bytes aligned and 0x14 leaves only 0xc bytes once aligned on 0x10
+        assert_eq!(alloc.first_fit(4, 0x10, 0), 0x30);
+        assert_eq!(alloc.best_fit(4, 0x10, 0), 0x30);
+        // NB: Aligned start inside the second hole, i.e. 0x18
+        assert_eq!(alloc.first_fit(8, 8, 0x10), 0x18);
+        assert_eq!(alloc.best_fit(8, 8, 0x10), 0x18);
+    }
+
+    #[test]
+    fn fallback_to_end() {
+        let alloc = allocator();
+        assert_eq!(alloc.first_fit(0x20, 4, 0), 0x30);
+        assert_eq!(alloc.best_fit(0x20, 4, 0), 0x30);
+        assert_eq!(alloc.end(), 0x30);
+        assert_eq!(Allocator::new().best_fit(4, 4, 0x6), 0x8);
+    }
+
+    #[test]
+    fn claim_reports_overlap() {
+        let mut alloc = allocator();
+        let mut diag = Diagnostics::new();
+        let ctx = SrcCtx::new("register `d`".to_string(), None);
+        alloc.claim("register", "d", 0x4..0x8, &ctx, &mut diag);
+        assert_eq!(diag.len(), 1);
+        assert_eq!(diag.first().map(|err| err.kind()), Some("AddressOverlap"));
+        alloc.claim("register", "e", 0x8..0xc, &ctx, &mut diag);
+        assert_eq!(diag.len(), 1);
+    }
+}
@@ -0,0 +1,269 @@
+//!
+//! Hierarchical register map
+//! A regmap could instantiate other regmap definitions as child blocks. Each block is a fully
+//! elaborated regmap placed at a base offset inside the parent address space.
+//!
+//! Child files are resolved relative to the including file. The base offset of a block is aligned
+//! on its range to keep the address decoding based on masks.
+//!
+
+use std::path::{Path, PathBuf};
+
+use getset::Getters;
+use serde::{Deserialize, Serialize};
+
+use super::alloc::Allocator;
+use super::diag::Diagnostics;
+use super::span::{SrcCtx, SrcSpan};
+use super::{
+    ElabOpt, Instance, Regmap, RegmapError, Section, align_on, expr, feature, include, parser,
+    span_in,
+};
+
+#[derive(Debug, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Block {
+    /// Instance name (i.e. with duplicate suffix)
+    name: String,
+    description: String,
+    /// Definition file of the child regmap, as written in the parent
+    file: String,
+    /// Definition file resolved against the parent folder, None if loaded from json
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    offset: usize,
+    range: usize,
+    regmap: Regmap,
+    #[serde(skip)]
+    span: Option<SrcSpan>,
+}
+
+/// Child definition shared by all the duplicated instances
+struct Child<'a> {
+    opt: &'a parser::BlockOpt,
+    path: PathBuf,
+    regmap: parser::RegmapOpt,
+}
+
+/// Key used to detect cyclic instantiation
+pub(super) fn canonical(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl Block {
+    /// Block of an already elaborated regmap (i.e. loaded from json)
+    pub(super) fn new(
+        name: String,
+        description: String,
+        file: String,
+        offset: usize,
+        range: usize,
+        regmap: Regmap,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            file,
+            path: None,
+            offset,
+            range,
+            regmap,
+            span: None,
+        }
+    }
+
+    /// Elaborate the blocks of a regmap
+    /// Blocks with a locked or explicit offset are placed first, then the others are placed after
+    /// the sections (or in the free holes with a fit strategy).
+    /// `chain` is the list of files currently being elaborated (parent included), used to detect
+    /// cycles
+    pub(super) fn from_opt(
+        parent: &parser::RegmapOpt,
+        regmap_offset: usize,
+        section: &[Section],
+        elab: &ElabOpt,
+        chain: &[PathBuf],
+        diag: &mut Diagnostics,
+    ) -> Vec<Self> {
+        let Some(blocks) = parent.block.as_ref() else {
+            return Vec::new();
+        };
+        let src = parent.src.as_ref();
+        let base_dir = src
+            .and_then(|src| Path::new(&src.name).parent())
+            .unwrap_or(Path::new(""));
+
+        // Load the child definitions
+        let mut children = Vec::with_capacity(blocks.len());
+        let mut instances = Vec::new();
+        for (name, spanned) in blocks.iter() {
+            let opt = spanned.get_ref();
+            let span = span_in(src, spanned);
+            let ctx = SrcCtx::new(format!("block `{name}`"), span.clone());
+            let path = base_dir.join(&opt.file);
+
+            if chain.contains(&canonical(&path)) {
+                diag.push(RegmapError::BlockCycle {
+                    chain: chain
+                        .iter()
+                        .chain(std::iter::once(&path))
+                        .map(|p| p.display().to_string())
+                        .collect(),
+                    ctx: ctx.with_hint(format!(
+                        "`{}` is already instantiated above this block",
+                        opt.file
+                    )),
+                });
+                continue;
+            }
+            let mut regmap = match parser::RegmapOpt::read_from(&path.display().to_string()) {
+                Ok(regmap) => regmap,
+                Err(err) => {
+                    diag.push(err);
+                    continue;
+                }
+            };
+            // NB: Range is required before the child elaboration
+            let nb_err = diag.len();
+            include::resolve(&mut regmap, diag);
+            feature::resolve(&mut regmap, elab, diag);
+            expr::resolve(&mut regmap, diag);
+            if diag.len() != nb_err {
+                continue;
+            }
+            if regmap.word_size_b != parent.word_size_b {
+                diag.push(RegmapError::WordSize);
+            }
+
+            // Handle duplication
+            // -> No duplication is 1 iteration without name extension
+            // NB: Duplication always have automatically computed offset
+            let range = regmap.range.value().next_power_of_two();
+            let duplicate = opt.duplicate.clone().unwrap_or(vec![String::new()]);
+            duplicate.iter().enumerate().for_each(|(i, s)| {
+                instances.push(Instance {
+                    elem: (format!("{name}{s}"), children.len(), span.clone(), i == 0),
+                    bytes_align: range,
+                    request: match opt.offset.as_ref() {
+                        Some(ofst) if i == 0 => Some(regmap_offset + ofst.value()),
+                        _ => None,
+                    },
+                    ctx: ctx.clone(),
+                });
+            });
+            children.push(Child { opt, path, regmap });
+        }
+
+        // Sections are already placed
+        let mut allocator = Allocator::new();
+        for sec in section.iter() {
+            allocator.reserve(*sec.offset()..sec.offset() + sec.range(), sec.name());
+        }
+
+        // Compute the base offset of each instance
+        let mut placed = Vec::with_capacity(instances.len());
+        let mut pending = Vec::new();
+        for inst in instances.iter() {
+            let name = &inst.elem.0;
+            let locked = elab.lock.as_ref().and_then(|lock| lock.block_offset(name));
+            let fixed = match (locked, inst.request) {
+                (Some(locked), Some(request)) if locked != request => {
+                    diag.push(RegmapError::LockedOffset {
+                        kind: "block".to_string(),
+                        name: name.clone(),
+                        locked,
+                        request,
+                        ctx: inst.ctx.with_hint(format!(
+                            "address is frozen in the lock file, restore offset 0x{:x} or remove the lock entry",
+                            locked.saturating_sub(regmap_offset)
+                        )),
+                    });
+                    Some(locked)
+                }
+                (locked, request) => locked.or(request),
+            };
+            match fixed {
+                Some(offset) => {
+                    // NB: Misaligned offset is reported by the child elaboration
+                    allocator.claim(
+                        "block",
+                        name,
+                        offset..offset + inst.bytes_align,
+                        &inst.ctx,
+                        diag,
+                    );
+                    placed.push((inst, offset));
+                }
+                None => pending.push(inst),
+            }
+        }
+        for inst in pending {
+            let offset = match elab.fit_strategy() {
+                // NB: Sequential allocation -> after all the already placed elements
+                None => align_on(inst.bytes_align, allocator.end().max(regmap_offset)),
+                Some(strategy) => {
+                    allocator.place(strategy, inst.bytes_align, inst.bytes_align, regmap_offset)
+                }
+            };
+            allocator.claim(
+                "block",
+                &inst.elem.0,
+                offset..offset + inst.bytes_align,
+                &inst.ctx,
+                diag,
+            );
+            placed.push((inst, offset));
+        }
+
+        // Elaborate the child regmaps at their base offset
+        let mut expanded_block = Vec::with_capacity(placed.len());
+        for (inst, offset) in placed {
+            let (name, child_idx, span, is_first) = &inst.elem;
+            let child = &children[*child_idx];
+            let mut regmap = child.regmap.clone();
+            regmap.offset = Some(offset.into());
+            let child_elab = ElabOpt {
+                lock: elab.lock.as_ref().map(|lock| lock.scoped(name)),
+                strategy: elab.strategy,
+                params: elab.params.clone(),
+                features: elab.features.clone(),
+                variant: elab.variant.clone(),
+                reserve_excluded: elab.reserve_excluded,
+            };
+            match Regmap::elaborate(&mut [regmap], &child_elab, chain) {
+                Ok(regmap) => expanded_block.push(Self {
+                    name: name.clone(),
+                    description: child
+                        .opt
+                        .description
+                        .clone()
+                        .unwrap_or_else(|| regmap.description.clone()),
+                    file: child.opt.file.clone(),
+                    path: Some(child.path.clone()),
+                    offset,
+                    range: inst.bytes_align,
+                    regmap,
+                    span: span.clone(),
+                }),
+                // NB: Errors of the child definition are only reported once
+                Err(child_diag) if *is_first => diag.extend(child_diag),
+                Err(_) => {}
+            }
+        }
+        // Sort by offset
+        expanded_block.sort_by_key(|a| a.offset);
+
+        expanded_block
+    }
+}
+
+impl std::fmt::Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "  name: {}", self.name)?;
+        writeln!(f, "  file: {}", self.file)?;
+        writeln!(f, "  offset: 0x{:x}", self.offset)?;
+        writeln!(f, "  range:  0x{:x}", self.range)?;
+        write!(f, "  Regmap: [\n{}]", self.regmap)?;
+        Ok(())
+    }
+}
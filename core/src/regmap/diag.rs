@@ -0,0 +1,141 @@
+//!
+//! Gather all the errors found while elaborating a register map
+//! Elaboration doesn't stop on the first error, instead errors are accumulated and a best-effort
+//! value is used to continue the checks.
+//!
+
+use super::RegmapError;
+use serde::Serialize;
+
+/// Structured view of an error
+/// Used for machine-readable output (i.e. json)
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub kind: String,
+    pub message: String,
+    pub element: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub hint: Option<String>,
+}
+
+impl From<&RegmapError> for Diagnostic {
+    fn from(err: &RegmapError) -> Self {
+        // Message is the error headline, the context is exposed in dedicated fields
+        let message = err
+            .to_string()
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let mut diag = Self {
+            kind: err.kind().to_string(),
+            message,
+            element: None,
+            file: None,
+            line: None,
+            column: None,
+            hint: None,
+        };
+        if let Some(ctx) = err.ctx() {
+            diag.element = Some(ctx.what().to_string());
+            diag.hint = ctx.hint().map(|h| h.to_string());
+            if let Some(span) = ctx.span() {
+                let (line, column) = span.line_col();
+                diag.file = Some(span.file().to_string());
+                diag.line = Some(line);
+                diag.column = Some(column);
+            }
+        }
+        if let RegmapError::TomlSyntax {
+            file, line, column, ..
+        } = err
+        {
+            diag.file = Some(file.clone());
+            diag.line = Some(*line);
+            diag.column = Some(*column);
+        }
+        diag
+    }
+}
+
+/// List of errors found during elaboration
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    errors: Vec<RegmapError>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, err: RegmapError) {
+        self.errors.push(err);
+    }
+
+    pub fn extend(&mut self, other: Self) {
+        self.errors.extend(other.errors);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, RegmapError> {
+        self.errors.iter()
+    }
+
+    /// Extract the first reported error
+    pub fn first(&self) -> Option<&RegmapError> {
+        self.errors.first()
+    }
+
+    /// Wrap value in a Result
+    /// Value is only returned if no error was reported
+    pub fn into_result<T>(self, value: T) -> Result<T, Self> {
+        if self.errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Structured view of the errors
+    pub fn to_diagnostic(&self) -> Vec<Diagnostic> {
+        self.errors.iter().map(Diagnostic::from).collect()
+    }
+
+    /// Serialize errors list in json
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_diagnostic())
+            .expect("Diagnostic serialization can not fail")
+    }
+}
+
+impl From<RegmapError> for Diagnostics {
+    fn from(err: RegmapError) -> Self {
+        Self { errors: vec![err] }
+    }
+}
+
+impl std::fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for err in self.errors.iter() {
+            writeln!(f, "Error: {err}\n")?;
+        }
+        write!(
+            f,
+            "{} error{} found",
+            self.errors.len(),
+            if self.errors.len() > 1 { "s" } else { "" }
+        )
+    }
+}
+
+impl std::error::Error for Diagnostics {}
@@ -0,0 +1,518 @@
+//!
+//! Constant expressions
+//! Integer properties (i.e. offset, range, size_b, offset_b, bytes_align and Cst default) could be
+//! given as a literal or as an expression string that uses the named constants of the `[const]`
+//! table.
+//!
+//! Supported syntax: integer literals (decimal, 0x, 0b), constant names, `+ - * / << >>`,
+//! parenthesis and the functions `log2(x)`, `clog2(x)` and `align(x, a)`.
+//! Expressions are resolved in place at the beginning of the elaboration, then properties are
+//! literals.
+//!
+
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::diag::Diagnostics;
+use super::parser::{DefaultOpt, RegmapOpt};
+use super::span::SrcCtx;
+use super::{RegmapError, span_in};
+
+/// Integer property: literal or expression
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Expr {
+    Lit(usize),
+    Text(String),
+}
+
+impl Expr {
+    /// Value of a resolved property
+    /// NB: Unresolved expressions only remain after an evaluation error, they are seen as 0 to
+    /// keep checking
+    pub fn value(&self) -> usize {
+        match self {
+            Self::Lit(val) => *val,
+            Self::Text(_) => 0,
+        }
+    }
+
+    /// Evaluate the expression in place
+    fn resolve(&mut self, consts: &IndexMap<String, usize>, ctx: &SrcCtx, diag: &mut Diagnostics) {
+        if let Self::Text(text) = self {
+            match eval(text, consts) {
+                Ok(val) => *self = Self::Lit(val),
+                Err(msg) => diag.push(RegmapError::Expr {
+                    expr: text.clone(),
+                    msg,
+                    ctx: ctx.clone(),
+                }),
+            }
+        }
+    }
+}
+
+impl From<usize> for Expr {
+    fn from(val: usize) -> Self {
+        Self::Lit(val)
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lit(val) => write!(f, "{val}"),
+            Self::Text(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// Resolve the constants and all the expressions of a regmap definition
+/// Constants are evaluated in definition order, thus a constant could only use the previous ones
+pub(super) fn resolve(regmap: &mut RegmapOpt, diag: &mut Diagnostics) {
+    let src = regmap.src.clone();
+    let mut consts = IndexMap::new();
+    for (name, spanned) in regmap.constant.iter_mut() {
+        let ctx = SrcCtx::new(format!("const `{name}`"), span_in(src.as_ref(), spanned));
+        if !is_ident(name) || FUNCTIONS.contains(&name.as_str()) {
+            diag.push(RegmapError::Expr {
+                expr: name.clone(),
+                msg: "invalid constant name".to_string(),
+                ctx: ctx.with_hint(
+                    "use an identifier (i.e. [A-Za-z_][A-Za-z0-9_]*) that isn't a function name"
+                        .to_string(),
+                ),
+            });
+            continue;
+        }
+        let expr = spanned.get_mut();
+        expr.resolve(&consts, &ctx, diag);
+        if let Expr::Lit(val) = expr {
+            consts.insert(name.clone(), *val);
+        }
+    }
+
+    let ctx = regmap.ctx();
+    regmap
+        .offset
+        .iter_mut()
+        .for_each(|e| e.resolve(&consts, &ctx, diag));
+    regmap.range.resolve(&consts, &ctx, diag);
+
+    for (sec_name, spanned) in regmap.section.iter_mut() {
+        // NB: Included sections have their own source file
+        let src = spanned.get_ref().src.clone().or_else(|| src.clone());
+        let ctx = SrcCtx::new(
+            format!("section `{sec_name}`"),
+            span_in(src.as_ref(), spanned),
+        );
+        let section = spanned.get_mut();
+        for expr in [
+            &mut section.offset,
+            &mut section.range,
+            &mut section.bytes_align,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            expr.resolve(&consts, &ctx, diag);
+        }
+        for (reg_name, spanned) in section.register.iter_mut() {
+            let ctx = SrcCtx::new(
+                format!("register `{reg_name}`"),
+                span_in(src.as_ref(), spanned),
+            );
+            let register = spanned.get_mut();
+            for expr in [&mut register.offset, &mut register.bytes_align]
+                .into_iter()
+                .flatten()
+            {
+                expr.resolve(&consts, &ctx, diag);
+            }
+            if let Some(DefaultOpt::Cst(expr)) = register.default.as_mut() {
+                expr.resolve(&consts, &ctx, diag);
+            }
+            for (field_name, spanned) in register.field.iter_mut().flatten() {
+                let ctx = SrcCtx::new(
+                    format!("field `{field_name}`"),
+                    span_in(src.as_ref(), spanned),
+                );
+                let field = spanned.get_mut();
+                field.size_b.resolve(&consts, &ctx, diag);
+                field
+                    .offset_b
+                    .iter_mut()
+                    .for_each(|e| e.resolve(&consts, &ctx, diag));
+                if let Some(DefaultOpt::Cst(expr)) = field.default.as_mut() {
+                    expr.resolve(&consts, &ctx, diag);
+                }
+            }
+        }
+    }
+
+    for (blk_name, spanned) in regmap.block.iter_mut().flatten() {
+        let ctx = SrcCtx::new(
+            format!("block `{blk_name}`"),
+            span_in(src.as_ref(), spanned),
+        );
+        spanned
+            .get_mut()
+            .offset
+            .iter_mut()
+            .for_each(|e| e.resolve(&consts, &ctx, diag));
+    }
+}
+
+/// Built-in functions, reserved names for constants
+const FUNCTIONS: [&str; 3] = ["log2", "clog2", "align"];
+
+pub(super) fn is_ident(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Num(usize),
+    Ident(String),
+    Op(&'static str),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Num(val) => write!(f, "{val}"),
+            Self::Ident(name) => write!(f, "{name}"),
+            Self::Op(op) => write!(f, "{op}"),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word = chars[start..i].iter().collect::<String>();
+            if c.is_ascii_digit() {
+                tokens.push(Token::Num(parse_num(&word)?));
+            } else {
+                tokens.push(Token::Ident(word));
+            }
+        } else {
+            let two = chars[i..chars.len().min(i + 2)].iter().collect::<String>();
+            let op = match (two.as_str(), c) {
+                ("<<", _) => "<<",
+                (">>", _) => ">>",
+                (_, '+') => "+",
+                (_, '-') => "-",
+                (_, '*') => "*",
+                (_, '/') => "/",
+                (_, '(') => "(",
+                (_, ')') => ")",
+                (_, ',') => ",",
+                _ => return Err(format!("unexpected character `{c}`")),
+            };
+            i += op.len();
+            tokens.push(Token::Op(op));
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_num(word: &str) -> Result<usize, String> {
+    let digits = word.replace('_', "");
+    let parsed = if let Some(hex) = digits.strip_prefix("0x") {
+        usize::from_str_radix(hex, 16)
+    } else if let Some(bin) = digits.strip_prefix("0b") {
+        usize::from_str_radix(bin, 2)
+    } else {
+        digits.parse::<usize>()
+    };
+    parsed.map_err(|_| format!("invalid integer `{word}`"))
+}
+
+/// Evaluate an expression with the given constants
+/// Return a human readable message on error
+pub fn eval(text: &str, consts: &IndexMap<String, usize>) -> Result<usize, String> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        pos: 0,
+        consts,
+    };
+    let val = parser.shift()?;
+    match parser.peek() {
+        None => Ok(val),
+        Some(tok) => Err(format!("unexpected `{tok}`")),
+    }
+}
+
+/// Recursive descent parser, lowest precedence first: shift, sum, product, atom
+struct Parser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    consts: &'a IndexMap<String, usize>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let tok = self.tokens.get(self.pos);
+        self.pos += 1;
+        tok
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            match self.peek() {
+                Some(tok) => Err(format!("expect `{op}`, get `{tok}`")),
+                None => Err(format!("expect `{op}`, get end of expression")),
+            }
+        }
+    }
+
+    fn shift(&mut self) -> Result<usize, String> {
+        let mut val = self.sum()?;
+        loop {
+            if self.eat("<<") {
+                let rhs = self.sum()?;
+                val = u32::try_from(rhs)
+                    .ok()
+                    .and_then(|rhs| val.checked_shl(rhs))
+                    .filter(|res| res >> rhs == val)
+                    .ok_or_else(|| format!("overflow in {val} << {rhs}"))?;
+            } else if self.eat(">>") {
+                let rhs = self.sum()?;
+                val = u32::try_from(rhs)
+                    .ok()
+                    .and_then(|rhs| val.checked_shr(rhs))
+                    .unwrap_or(0);
+            } else {
+                return Ok(val);
+            }
+        }
+    }
+
+    fn sum(&mut self) -> Result<usize, String> {
+        let mut val = self.product()?;
+        loop {
+            if self.eat("+") {
+                let rhs = self.product()?;
+                val = val
+                    .checked_add(rhs)
+                    .ok_or_else(|| format!("overflow in {val} + {rhs}"))?;
+            } else if self.eat("-") {
+                let rhs = self.product()?;
+                val = val
+                    .checked_sub(rhs)
+                    .ok_or_else(|| format!("negative result in {val} - {rhs}"))?;
+            } else {
+                return Ok(val);
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<usize, String> {
+        let mut val = self.atom()?;
+        loop {
+            if self.eat("*") {
+                let rhs = self.atom()?;
+                val = val
+                    .checked_mul(rhs)
+                    .ok_or_else(|| format!("overflow in {val} * {rhs}"))?;
+            } else if self.eat("/") {
+                let rhs = self.atom()?;
+                val = val
+                    .checked_div(rhs)
+                    .ok_or_else(|| format!("division by zero in {val} / {rhs}"))?;
+            } else {
+                return Ok(val);
+            }
+        }
+    }
+
+    fn atom(&mut self) -> Result<usize, String> {
+        match self.next().cloned() {
+            Some(Token::Num(val)) => Ok(val),
+            Some(Token::Op("(")) => {
+                let val = self.shift()?;
+                self.expect(")")?;
+                Ok(val)
+            }
+            Some(Token::Ident(name)) if FUNCTIONS.contains(&name.as_str()) => {
+                self.expect("(")?;
+                let mut args = vec![self.shift()?];
+                while self.eat(",") {
+                    args.push(self.shift()?);
+                }
+                self.expect(")")?;
+                call(&name, &args)
+            }
+            Some(Token::Ident(name)) => self
+                .consts
+                .get(&name)
+                .copied()
+                .ok_or_else(|| format!("unknown constant `{name}`")),
+            Some(tok) => Err(format!("unexpected `{tok}`")),
+            None => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn call(name: &str, args: &[usize]) -> Result<usize, String> {
+    match (name, args) {
+        ("log2", [0]) => Err("log2(0) is undefined".to_string()),
+        ("log2", [x]) => Ok(x.ilog2() as usize),
+        // NB: Same convention as SystemVerilog $clog2 (i.e. clog2(0) = 0)
+        ("clog2", [x]) => Ok(x
+            .checked_next_power_of_two()
+            .map_or(usize::BITS, |p| p.trailing_zeros()) as usize),
+        ("align", [_, 0]) => Err("align on 0".to_string()),
+        ("align", [x, a]) => x
+            .checked_next_multiple_of(*a)
+            .ok_or_else(|| format!("overflow in align({x}, {a})")),
+        ("log2" | "clog2", _) => Err(format!("{name}(x) expects 1 argument")),
+        _ => Err(format!("{name}(x, a) expects 2 arguments")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn consts() -> IndexMap<String, usize> {
+        IndexMap::from([("N".to_string(), 8), ("W".to_string(), 32)])
+    }
+
+    #[test]
+    fn literals() {
+        let consts = consts();
+        assert_eq!(eval("42", &consts), Ok(42));
+        assert_eq!(eval("0x2a", &consts), Ok(42));
+        assert_eq!(eval("0b101010", &consts), Ok(42));
+        assert!(eval("0xzz", &consts).is_err());
+    }
+
+    #[test]
+    fn precedence() {
+        let consts = consts();
+        assert_eq!(eval("1 + 2 * 3", &consts), Ok(7));
+        assert_eq!(eval("(1 + 2) * 3", &consts), Ok(9));
+        assert_eq!(eval("10 - 4 - 3", &consts), Ok(3));
+        assert_eq!(eval("64 / 4 / 2", &consts), Ok(8));
+        // NB: Shift has the lowest precedence, as in SystemVerilog
+        assert_eq!(eval("1 << 2 + 1", &consts), Ok(8));
+        assert_eq!(eval("0x100 >> N / 2", &consts), Ok(0x10));
+        assert_eq!(eval("W * N + align(5, 4)", &consts), Ok(264));
+        assert_eq!(eval("clog2(W) + log2(N)", &consts), Ok(8));
+    }
+
+    #[test]
+    fn overflow() {
+        let consts = consts();
+        assert!(eval("1 << 64", &consts).is_err());
+        assert!(eval(&format!("{} + 1", usize::MAX), &consts).is_err());
+        assert!(eval(&format!("{} * 2", usize::MAX), &consts).is_err());
+        assert!(eval("1 - 2", &consts).is_err());
+        assert_eq!(eval("1 >> 64", &consts), Ok(0));
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let consts = consts();
+        assert!(eval("1 / 0", &consts).is_err());
+        assert!(eval("N / (W - 32)", &consts).is_err());
+        assert!(eval("align(3, 0)", &consts).is_err());
+        assert!(eval("log2(0)", &consts).is_err());
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let consts = consts();
+        assert!(eval("(1 + 2", &consts).is_err());
+        assert!(eval("1 +", &consts).is_err());
+        assert!(eval("1 2", &consts).is_err());
+        assert!(eval("align(1)", &consts).is_err());
+    }
+
+    #[test]
+    fn unknown_const() {
+        assert_eq!(
+            eval("N + M", &consts()),
+            Err("unknown constant `M`".to_string())
+        );
+    }
+
+    /// Resolve the `[const]` table of a regmap snippet and list the reported expressions
+    fn resolve_consts(table: &str) -> (IndexMap<String, Expr>, Vec<String>) {
+        let content = format!(
+            "module_name=\"test\"\ndescription=\"t\"\nword_size_b=32\nrange=0x100\next_pkg=[]\n[const]\n{table}"
+        );
+        let mut regmap = RegmapOpt::from_toml_str("test.toml", &content).expect("Valid toml");
+        let mut diag = Diagnostics::new();
+        resolve(&mut regmap, &mut diag);
+        let consts = regmap
+            .constant
+            .iter()
+            .map(|(name, spanned)| (name.clone(), spanned.get_ref().clone()))
+            .collect();
+        let errors = diag
+            .iter()
+            .filter_map(|err| match err {
+                RegmapError::Expr { expr, .. } => Some(expr.clone()),
+                _ => None,
+            })
+            .collect();
+        (consts, errors)
+    }
+
+    #[test]
+    fn const_chain() {
+        let (consts, errors) = resolve_consts("A = 4\nB = \"A * 2\"\nC = \"B + A\"\n");
+        assert!(errors.is_empty());
+        assert_eq!(consts["C"], Expr::Lit(12));
+    }
+
+    #[test]
+    fn cyclic_const() {
+        // NB: Constants only use the previous ones, thus a cycle is reported as unknown constant
+        let (consts, errors) = resolve_consts("A = \"B + 1\"\nB = \"A + 1\"\n");
+        assert_eq!(errors, ["B + 1", "A + 1"]);
+        assert!(consts.values().all(|expr| matches!(expr, Expr::Text(_))));
+        let (_, errors) = resolve_consts("A = \"A\"\n");
+        assert_eq!(errors, ["A"]);
+    }
+
+    #[test]
+    fn invalid_const_name() {
+        let (_, errors) = resolve_consts("log2 = 3\n");
+        assert_eq!(errors, ["log2"]);
+    }
+}
@@ -0,0 +1,144 @@
+//!
+//! Build variants
+//! Sections, registers and fields could hold an `if` predicate on the enabled features, thus a
+//! single definition covers all the variants of an IP. Features are enabled one by one or with a
+//! named variant of the `[variant]` table.
+//!
+//! Excluded elements are dropped before the address inference. Optionally, they are kept as
+//! read-as-zero reserved elements (i.e. their addresses/bits are allocated but hold nothing),
+//! thus the layout doesn't shift between variants.
+//!
+//! Predicate syntax: feature names, `!` negation, `&&` and `||` (`&&` binds tighter). Parenthesis
+//! aren't supported.
+//!
+
+use super::diag::Diagnostics;
+use super::expr::is_ident;
+use super::parser::RegmapOpt;
+use super::span::SrcCtx;
+use super::{ElabOpt, RegmapError, span_in};
+
+/// Filter the elements of a regmap definition with the enabled features
+pub(super) fn resolve(regmap: &mut RegmapOpt, elab: &ElabOpt, diag: &mut Diagnostics) {
+    let mut features = elab.features.clone();
+    if let Some(variant) = elab.variant.as_ref() {
+        match regmap.variant.get(variant) {
+            Some(set) => features.extend(set.iter().cloned()),
+            // NB: Definitions without variants (i.e. shared blocks) only see the enabled features
+            None if regmap.variant.is_empty() => {}
+            None => diag.push(RegmapError::UnknownVariant {
+                variant: variant.clone(),
+                ctx: regmap.ctx().with_hint(format!(
+                    "available variants are [{}]",
+                    regmap
+                        .variant
+                        .keys()
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            }),
+        }
+    }
+    let reserve = elab.reserve_excluded;
+
+    // Check a predicate, invalid ones are reported and seen as true to keep checking
+    let mut holds = |cond: &Option<String>, ctx: &SrcCtx| match cond.as_ref() {
+        Some(cond) => eval(cond, &features).unwrap_or_else(|msg| {
+            diag.push(RegmapError::Predicate {
+                cond: cond.clone(),
+                msg,
+                ctx: ctx.with_hint(
+                    "use feature names with `!`, `&&` and `||` (e.g. \"debug && !lite\")"
+                        .to_string(),
+                ),
+            });
+            true
+        }),
+        None => true,
+    };
+
+    let src = regmap.src.clone();
+    regmap.section.retain(|sec_name, spanned| {
+        let sec_src = spanned.get_ref().src.clone().or_else(|| src.clone());
+        let ctx = SrcCtx::new(
+            format!("section `{sec_name}`"),
+            span_in(sec_src.as_ref(), spanned),
+        );
+        let section = spanned.get_mut();
+        let sec_kept = holds(&section.cond, &ctx);
+        if !sec_kept && !reserve {
+            return false;
+        }
+        section.excluded |= !sec_kept;
+
+        section.register.retain(|reg_name, spanned| {
+            let ctx = SrcCtx::new(
+                format!("register `{reg_name}`"),
+                span_in(sec_src.as_ref(), spanned),
+            );
+            let register = spanned.get_mut();
+            let kept = holds(&register.cond, &ctx);
+            if !kept && !reserve {
+                return false;
+            }
+            // NB: Registers of an excluded section are excluded too
+            register.excluded |= !kept || !sec_kept;
+
+            if let Some(fields) = register.field.as_mut() {
+                fields.retain(|field_name, spanned| {
+                    let ctx = SrcCtx::new(
+                        format!("field `{field_name}`"),
+                        span_in(sec_src.as_ref(), spanned),
+                    );
+                    let field = spanned.get_mut();
+                    let kept = holds(&field.cond, &ctx);
+                    field.excluded |= !kept;
+                    kept || reserve
+                });
+            }
+            true
+        });
+        true
+    });
+}
+
+/// Check that a variant exists in one of the definitions
+/// NB: Definitions could omit the `[variant]` table, but at least one of them must select it
+pub(super) fn check_variant(regmaps: &[RegmapOpt], elab: &ElabOpt, diag: &mut Diagnostics) {
+    if let (Some(variant), Some(first)) = (elab.variant.as_ref(), regmaps.first()) {
+        if regmaps.iter().all(|regmap| regmap.variant.is_empty()) {
+            diag.push(RegmapError::UnknownVariant {
+                variant: variant.clone(),
+                ctx: first
+                    .ctx()
+                    .with_hint("no `[variant]` table is defined".to_string()),
+            });
+        }
+    }
+}
+
+/// Evaluate a predicate with the enabled features
+pub fn eval(cond: &str, features: &[String]) -> Result<bool, String> {
+    let mut any = false;
+    for term in cond.split("||") {
+        let mut all = true;
+        for factor in term.split("&&") {
+            let factor = factor.trim();
+            let (negate, name) = match factor.strip_prefix('!') {
+                Some(name) => (true, name.trim()),
+                None => (false, factor),
+            };
+            if !is_ident(name) {
+                return Err(if name.is_empty() {
+                    "missing feature name".to_string()
+                } else {
+                    format!("invalid feature name `{name}`")
+                });
+            }
+            all &= features.iter().any(|f| f == name) != negate;
+        }
+        any |= all;
+    }
+    Ok(any)
+}
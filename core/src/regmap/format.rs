@@ -0,0 +1,455 @@
+//!
+//! Canonical TOML style of the register map definition
+//! Used to rewrite user files in a consistent style (i.e. `fmt`) and to export an elaborated
+//! regmap back in TOML with all the inferred properties explicit.
+//!
+//! Canonical style is:
+//!  * `key = value` with aligned `=` inside a table
+//!  * keys of register tables indented by two spaces, others not indented
+//!  * inline tables and arrays on a single line with `{ a = 1, b = 2 }` and `[a, b]` spacing
+//!  * one blank line before each table header, at most one blank line between keys
+//!  * comments are kept, only their indentation is normalized
+//!
+
+use std::path::{Component, Path, PathBuf};
+
+use toml_edit::{DocumentMut, InlineTable, Item, Table, Value};
+
+use super::span::SrcFile;
+use super::{DefaultVal, Regmap, RegmapError};
+
+/// Indentation of the keys in register tables
+const REG_INDENT: &str = "  ";
+
+/// Rewrite the content of a TOML file in the canonical style
+pub fn canonical(file: &str, content: &str) -> Result<String, RegmapError> {
+    let mut doc = content.parse::<DocumentMut>().map_err(|err| {
+        let src = SrcFile::new(file, content);
+        let (line, column) = src.line_col(err.span().map(|s| s.start).unwrap_or(0));
+        RegmapError::TomlSyntax {
+            file: file.to_string(),
+            line,
+            column,
+            msg: err.message().to_string(),
+        }
+    })?;
+    fmt_document(&mut doc);
+    Ok(doc.to_string())
+}
+
+/// Apply canonical style on a parsed document
+pub fn fmt_document(doc: &mut DocumentMut) {
+    fmt_table(doc.as_table_mut(), &[], true);
+    let trailing = normalize_comments(doc.trailing().as_str().unwrap_or_default(), "", false);
+    doc.set_trailing(trailing);
+}
+
+/// Format a table and recursively all its sub-tables
+/// `path` is the header path of the table, used to select the indentation
+/// Return true if a header was written (i.e. by the table or one of its sub-tables)
+fn fmt_table(table: &mut Table, path: &[String], first: bool) -> bool {
+    // Header decor: comments kept, separated from previous table by a blank line
+    if !path.is_empty() && !table.is_implicit() {
+        let prefix = normalize_comments(
+            table
+                .decor()
+                .prefix()
+                .and_then(|p| p.as_str())
+                .unwrap_or_default(),
+            "",
+            false,
+        );
+        let suffix = normalize_trailing(
+            table
+                .decor()
+                .suffix()
+                .and_then(|s| s.as_str())
+                .unwrap_or_default(),
+        );
+        let blank = if first { "" } else { "\n" };
+        table.decor_mut().set_prefix(format!("{blank}{prefix}"));
+        table.decor_mut().set_suffix(suffix);
+    }
+
+    // NB: Register tables are `section.<name>.register.<name>`, register instances
+    //     `section.<name>.instance.<name>` and templates `template.register.<name>` (also found
+    //     in section templates)
+    let indent =
+        if path.len() >= 3 && matches!(path[path.len() - 2].as_str(), "register" | "instance") {
+            REG_INDENT
+        } else {
+            ""
+        };
+    let width = key_width(table, "");
+    fmt_keys(table, "", indent, width, &mut false);
+
+    // Recurse in header tables
+    let mut first_sub = first && table.iter().all(|(_, item)| !item.is_value());
+    let mut header = !path.is_empty() && !table.is_implicit();
+    for (key, item) in table.iter_mut() {
+        let mut sub_path = path.to_vec();
+        sub_path.push(key.get().to_string());
+        match item {
+            Item::Table(sub) if !sub.is_dotted() => {
+                // NB: Implicit tables only write the headers of their sub-tables
+                let sub_header = fmt_table(sub, &sub_path, first_sub);
+                first_sub = first_sub && !sub_header;
+                header |= sub_header;
+            }
+            Item::ArrayOfTables(array) => {
+                for sub in array.iter_mut() {
+                    fmt_table(sub, &sub_path, first_sub);
+                    first_sub = false;
+                    header = true;
+                }
+            }
+            _ => {}
+        }
+    }
+    header
+}
+
+/// Width of the longest key of the table body (dotted keys included)
+fn key_width(table: &Table, prefix: &str) -> usize {
+    table
+        .iter()
+        .filter_map(|(key, item)| table.key(key).map(|key| (key, item)))
+        .map(|(key, item)| match item {
+            // NB: Width is the displayed one (i.e. with quotes)
+            Item::Value(_) => prefix.len() + key.display_repr().len(),
+            Item::Table(sub) if sub.is_dotted() => {
+                key_width(sub, &format!("{prefix}{}.", key.display_repr()))
+            }
+            _ => 0,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Format the `key = value` lines of a table body
+/// Blank lines are kept between keys, except before the first one (i.e. `keep_blank`)
+fn fmt_keys(table: &mut Table, prefix: &str, indent: &str, width: usize, keep_blank: &mut bool) {
+    for (mut key, item) in table.iter_mut() {
+        match item {
+            Item::Value(value) => {
+                let comments = normalize_comments(
+                    key.leaf_decor()
+                        .prefix()
+                        .and_then(|p| p.as_str())
+                        .unwrap_or_default(),
+                    indent,
+                    *keep_blank,
+                );
+                *keep_blank = true;
+                let pad = width - (prefix.len() + key.display_repr().len());
+                key.leaf_decor_mut()
+                    .set_prefix(format!("{comments}{indent}"));
+                key.leaf_decor_mut().set_suffix(" ".repeat(pad + 1));
+                key.dotted_decor_mut().clear();
+                fmt_value(value);
+                let suffix = normalize_trailing(
+                    value
+                        .decor()
+                        .suffix()
+                        .and_then(|s| s.as_str())
+                        .unwrap_or_default(),
+                );
+                value.decor_mut().set_prefix(" ");
+                value.decor_mut().set_suffix(suffix);
+            }
+            Item::Table(sub) if sub.is_dotted() => {
+                // NB: Line decor is carried by the leaf key, intermediate keys are left bare
+                key.leaf_decor_mut().set_prefix("");
+                key.leaf_decor_mut().set_suffix("");
+                key.dotted_decor_mut().clear();
+                fmt_keys(
+                    sub,
+                    &format!("{prefix}{}.", key.display_repr()),
+                    indent,
+                    width,
+                    keep_blank,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Format inline values on a single line
+fn fmt_value(value: &mut Value) {
+    match value {
+        Value::InlineTable(table) => {
+            table.iter_mut().for_each(|(_, v)| fmt_value(v));
+            table.fmt();
+        }
+        Value::Array(array) => {
+            array.iter_mut().for_each(fmt_value);
+            array.fmt();
+        }
+        _ => {}
+    }
+}
+
+/// Keep comment lines with the given indentation
+/// Consecutive blank lines are merged in one, leading ones are only kept with `keep_blank`
+fn normalize_comments(raw: &str, indent: &str, keep_blank: bool) -> String {
+    let mut lines = raw.split('\n').collect::<Vec<_>>();
+    // Last line is the indentation of the element itself
+    lines.pop();
+    let mut normalized = String::new();
+    let mut blank = !keep_blank;
+    for line in lines.iter().map(|l| l.trim()) {
+        if line.is_empty() {
+            if !blank {
+                normalized.push('\n');
+            }
+            blank = true;
+        } else {
+            normalized.push_str(&format!("{indent}{line}\n"));
+            blank = false;
+        }
+    }
+    normalized
+}
+
+/// Keep trailing comment with a single space separator
+fn normalize_trailing(raw: &str) -> String {
+    match raw.trim() {
+        "" => String::new(),
+        comment => format!(" {comment}"),
+    }
+}
+
+/// Hexadecimal integer value
+fn hex(val: usize) -> Value {
+    format!("0x{val:x}")
+        .parse::<Value>()
+        .expect("Hexadecimal literal is a valid TOML value")
+}
+
+fn default_value(dflt: &DefaultVal) -> Value {
+    let mut table = InlineTable::new();
+    match dflt {
+        DefaultVal::Cst(val) => {
+            table.insert("Cst", hex(*val));
+        }
+        DefaultVal::Param(param) => {
+            table.insert("Param", Value::from(param.as_str()));
+        }
+        DefaultVal::ParamsField { params, name_val } => {
+            let mut inner = InlineTable::new();
+            inner.insert("params", Value::Array(params.iter().collect()));
+            inner.insert(
+                "name_val",
+                Value::Array(
+                    name_val
+                        .iter()
+                        .map(|(n, v)| Value::Array([n, v].into_iter().collect()))
+                        .collect(),
+                ),
+            );
+            table.insert("ParamsField", Value::InlineTable(inner));
+        }
+    }
+    Value::InlineTable(table)
+}
+
+impl Regmap {
+    /// Export the elaborated regmap in TOML, written in the `output_dir` folder
+    /// All inferred properties are explicit (i.e. offsets, ranges, alignments, fields position
+    /// and defaults) and duplications are expanded, thus it could be used to freeze a layout.
+    /// Child blocks still reference their definition file, rewritten relative to `output_dir`.
+    /// Parsing it back gives the same regmap as long as the child files are kept in place and
+    /// elaborated with the same options.
+    pub fn to_toml(&self, output_dir: &Path) -> String {
+        let mut doc = DocumentMut::new();
+        doc["module_name"] = toml_edit::value(self.module_name.as_str());
+        doc["description"] = toml_edit::value(self.description.as_str());
+        doc["word_size_b"] = toml_edit::value(self.word_size_b as i64);
+        doc["offset"] = Item::Value(hex(self.offset));
+        doc["range"] = Item::Value(hex(self.range));
+        let mut ext_pkg = self.ext_pkg.iter().collect::<Vec<_>>();
+        ext_pkg.sort();
+        doc["ext_pkg"] = Item::Value(Value::Array(ext_pkg.into_iter().collect()));
+
+        let mut sections = Table::new();
+        sections.set_implicit(true);
+        for sec in self.section.iter() {
+            let mut sec_table = Table::new();
+            sec_table["description"] = toml_edit::value(sec.description().as_str());
+            sec_table["offset"] = Item::Value(hex(sec.offset() - self.offset));
+            sec_table["range"] = Item::Value(hex(*sec.range()));
+            sec_table["bytes_align"] = Item::Value(hex(*sec.bytes_align()));
+            if let Some(policy) = sec.reserved() {
+                sec_table["reserved"] = toml_edit::value(policy.to_string());
+            }
+
+            let mut registers = Table::new();
+            registers.set_implicit(true);
+            for reg in sec.register().iter() {
+                let mut reg_table = Table::new();
+                reg_table["description"] = toml_edit::value(reg.description().as_str());
+                // NB: Access rights of a reserved register are implied by its policy
+                match reg.reserved() {
+                    Some(policy) => reg_table["reserved"] = toml_edit::value(policy.to_string()),
+                    None => {
+                        reg_table["owner"] = toml_edit::value(format!("{:?}", reg.owner()));
+                        reg_table["read_access"] =
+                            toml_edit::value(format!("{:?}", reg.read_access()));
+                        reg_table["write_access"] =
+                            toml_edit::value(format!("{:?}", reg.write_access()));
+                    }
+                }
+                reg_table["offset"] = Item::Value(hex(reg.offset() - sec.offset()));
+                reg_table["bytes_align"] = Item::Value(hex(*reg.bytes_align()));
+                match reg.field() {
+                    Some(fields) => {
+                        let mut field_table = Table::new();
+                        field_table.set_dotted(true);
+                        for field in fields.iter() {
+                            let mut inline = InlineTable::new();
+                            inline.insert("size_b", Value::from(*field.size_b() as i64));
+                            if let Some(param) = field.size_param() {
+                                inline.insert("size_param", Value::from(param.as_str()));
+                            }
+                            inline.insert("offset_b", Value::from(*field.offset_b() as i64));
+                            if let Some(dflt) = field.default() {
+                                inline.insert("default", default_value(dflt));
+                            }
+                            if let Some(policy) = field.reserved() {
+                                inline.insert("reserved", Value::from(policy.to_string()));
+                            }
+                            inline.insert("description", Value::from(field.description().as_str()));
+                            field_table
+                                .insert(field.name(), Item::Value(Value::InlineTable(inline)));
+                        }
+                        reg_table["field"] = Item::Table(field_table);
+                    }
+                    // NB: With fields, register default is inferred from them
+                    None => reg_table["default"] = Item::Value(default_value(reg.default())),
+                }
+                registers.insert(reg.name(), Item::Table(reg_table));
+            }
+            sec_table["register"] = Item::Table(registers);
+            sections.insert(sec.name(), Item::Table(sec_table));
+        }
+        doc["section"] = Item::Table(sections);
+
+        // NB: Blocks keep their definition file, relative to the output folder
+        if !self.block.is_empty() {
+            let mut blocks = Table::new();
+            blocks.set_implicit(true);
+            for blk in self.block.iter() {
+                let mut blk_table = Table::new();
+                blk_table["description"] = toml_edit::value(blk.description().as_str());
+                let file = match blk.path() {
+                    Some(path) => relative_to(path, output_dir).display().to_string(),
+                    None => blk.file().clone(),
+                };
+                blk_table["file"] = toml_edit::value(file);
+                blk_table["offset"] = Item::Value(hex(blk.offset() - self.offset));
+                blocks.insert(blk.name(), Item::Table(blk_table));
+            }
+            doc["block"] = Item::Table(blocks);
+        }
+
+        fmt_document(&mut doc);
+        doc.to_string()
+    }
+}
+
+/// Absolute path without `.` and `..` components
+/// NB: Lexical only, the path doesn't have to exist (i.e. output folder not yet created)
+fn normalize(path: &Path) -> PathBuf {
+    let abs = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut norm = PathBuf::new();
+    for comp in abs.components() {
+        match comp {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                norm.pop();
+            }
+            comp => norm.push(comp),
+        }
+    }
+    norm
+}
+
+/// Path of `target` relative to the `dir` folder
+fn relative_to(target: &Path, dir: &Path) -> PathBuf {
+    let (target, dir) = (normalize(target), normalize(dir));
+    let common = target
+        .components()
+        .zip(dir.components())
+        .take_while(|(t, d)| t == d)
+        .count();
+    let mut rel = PathBuf::new();
+    dir.components().skip(common).for_each(|_| rel.push(".."));
+    target
+        .components()
+        .skip(common)
+        .for_each(|comp| rel.push(comp));
+    rel
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regmap::parser::RegmapOpt;
+
+    #[test]
+    fn toml_round_trip() {
+        let content = r#"
+module_name="test"
+description="Unit test regmap"
+word_size_b=32
+range=0x100
+ext_pkg=[]
+[section.A]
+description="a"
+[section.A.register.R0]
+description="r0"
+owner="User"
+read_access="Read"
+write_access="Write"
+[section.A.register.R1]
+description="r1"
+owner="User"
+read_access="Read"
+write_access="Write"
+bytes_align=0x10
+duplicate=["_a", "_b"]
+"#;
+        let elaborate = |file: &str, content: &str| {
+            let mut opt = RegmapOpt::from_toml_str(file, content).expect("Valid toml");
+            Regmap::from_opt(std::slice::from_mut(&mut opt)).expect("Valid regmap")
+        };
+        let offsets = |regmap: &Regmap| {
+            regmap
+                .section()
+                .iter()
+                .flat_map(|sec| sec.register().iter())
+                .map(|reg| (reg.name().clone(), *reg.offset(), *reg.bytes_align()))
+                .collect::<Vec<_>>()
+        };
+
+        let regmap = elaborate("test.toml", content);
+        let exported = regmap.to_toml(Path::new(""));
+        // NB: Keys are aligned by the canonical style
+        assert!(
+            exported
+                .lines()
+                .any(|line| { line.split_whitespace().collect::<String>() == "bytes_align=0x10" })
+        );
+        let parsed = elaborate("exported.toml", &exported);
+        assert_eq!(offsets(&parsed), offsets(&regmap));
+        assert_eq!(
+            offsets(&regmap),
+            [
+                ("R0".to_string(), 0x0, 4),
+                ("R1_a".to_string(), 0x10, 0x10),
+                ("R1_b".to_string(), 0x20, 0x10),
+            ]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//!
+//! Include and templates
+//! A definition could merge other toml files with `include` and reuse register/section shapes
+//! declared in the `[template]` table. Both are resolved in place at the beginning of the
+//! elaboration, thus the remaining steps only see plain sections and registers.
+//!
+//! Included files are fragments: they only hold `include`, `template`, `section` and `instance`
+//! entries, paths are relative to the including file. Their content is placed before the one of
+//! the including file.
+//!
+//! Templates are raw tables, an instance merges its overrides in a copy of the template (tables
+//! are merged recursively, other values replaced) and the result is checked as a plain
+//! register/section.
+//!
+
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use toml::Spanned;
+
+use super::block::canonical;
+use super::diag::Diagnostics;
+use super::parser::{
+    FragmentOpt, IncludeOpt, InstanceOpt, RegisterOpt, RegmapOpt, SectionOpt, unknown_key,
+};
+use super::span::{SrcCtx, SrcFile};
+use super::{RegmapError, span_in};
+
+/// Instance of a section template with its source file
+type SectionInstance = (String, Spanned<InstanceOpt>, Option<Arc<SrcFile>>);
+
+/// Content gathered from the included files
+#[derive(Default)]
+struct Included {
+    template_register: IndexMap<String, Spanned<toml::Table>>,
+    template_section: IndexMap<String, Spanned<toml::Table>>,
+    section: IndexMap<String, Spanned<SectionOpt>>,
+    instance: Vec<SectionInstance>,
+    files: Vec<PathBuf>,
+}
+
+/// Resolve the includes and expand the template instances of a regmap definition
+pub(super) fn resolve(regmap: &mut RegmapOpt, diag: &mut Diagnostics) {
+    let src = regmap.src.clone();
+    let root = src
+        .as_ref()
+        .map(|src| vec![PathBuf::from(&src.name)])
+        .unwrap_or_default();
+
+    // Load the included files
+    // NB: Taken, thus resolving twice (i.e. child block) doesn't include them again
+    let mut included = Included::default();
+    if let Some(include) = regmap.include.take() {
+        load(&include, src.as_ref(), &root, &mut included, diag);
+    }
+    regmap.included.append(&mut included.files);
+
+    // Merge them in front of the regmap content
+    let template = &mut regmap.template;
+    template.register = merge_map(
+        "register template",
+        std::mem::take(&mut included.template_register),
+        std::mem::take(&mut template.register),
+        src.as_ref(),
+        diag,
+    );
+    template.section = merge_map(
+        "section template",
+        std::mem::take(&mut included.template_section),
+        std::mem::take(&mut template.section),
+        src.as_ref(),
+        diag,
+    );
+    regmap.section = merge_map(
+        "section",
+        std::mem::take(&mut included.section),
+        std::mem::take(&mut regmap.section),
+        src.as_ref(),
+        diag,
+    );
+    let mut instances = std::mem::take(&mut included.instance);
+    instances.extend(
+        std::mem::take(&mut regmap.instance)
+            .into_iter()
+            .map(|(name, inst)| (name, inst, src.clone())),
+    );
+
+    // Expand the section instances, then the register instances of all the sections
+    // NB: A section template could hold register instances
+    for (name, spanned, inst_src) in instances {
+        let ctx = SrcCtx::new(
+            format!("section `{name}`"),
+            span_in(inst_src.as_ref(), &spanned),
+        );
+        if regmap.section.contains_key(&name) {
+            diag.push(duplicate("section", &name, &ctx));
+            continue;
+        }
+        let Some(mut section) =
+            instantiate::<SectionOpt>(&regmap.template.section, spanned.get_ref(), &ctx, diag)
+        else {
+            continue;
+        };
+        section.src = inst_src;
+        let span = spanned.span();
+        section.register = respan_registers(std::mem::take(&mut section.register), &span);
+        section.instance = std::mem::take(&mut section.instance)
+            .into_iter()
+            .map(|(name, inst)| (name, Spanned::new(span.clone(), inst.into_inner())))
+            .collect();
+        regmap.section.insert(name, Spanned::new(span, section));
+    }
+
+    for (sec_name, spanned) in regmap.section.iter_mut() {
+        let section = spanned.get_mut();
+        let sec_src = section.src.clone().or_else(|| src.clone());
+        for (name, spanned) in std::mem::take(&mut section.instance) {
+            let ctx = SrcCtx::new(
+                format!("register `{sec_name}::{name}`"),
+                span_in(sec_src.as_ref(), &spanned),
+            );
+            if section.register.contains_key(&name) {
+                diag.push(duplicate("register", &name, &ctx));
+                continue;
+            }
+            let Some(mut register) = instantiate::<RegisterOpt>(
+                &regmap.template.register,
+                spanned.get_ref(),
+                &ctx,
+                diag,
+            ) else {
+                continue;
+            };
+            let span = spanned.span();
+            respan_fields(&mut register, &span);
+            section.register.insert(name, Spanned::new(span, register));
+        }
+    }
+}
+
+/// Load the included files, depth first
+/// `chain` is the list of files currently being included (root included), used to detect cycles
+fn load(
+    include: &Spanned<IncludeOpt>,
+    src: Option<&Arc<SrcFile>>,
+    chain: &[PathBuf],
+    included: &mut Included,
+    diag: &mut Diagnostics,
+) {
+    let base_dir = src
+        .and_then(|src| Path::new(&src.name).parent())
+        .unwrap_or(Path::new(""));
+    let span = span_in(src, include);
+
+    for file in include.get_ref().files() {
+        let path = base_dir.join(file);
+        let mut sub_chain = chain.to_vec();
+        sub_chain.push(path.clone());
+        let ctx = SrcCtx::new(format!("include `{file}`"), span.clone());
+        let error = |msg: String, hint: Option<String>| RegmapError::Include {
+            file: file.clone(),
+            chain: sub_chain.iter().map(|p| p.display().to_string()).collect(),
+            msg,
+            ctx: match hint {
+                Some(hint) => ctx.with_hint(hint),
+                None => ctx.clone(),
+            },
+        };
+
+        let key = canonical(&path);
+        if chain.iter().any(|p| canonical(p) == key) {
+            diag.push(error(
+                "cyclic include".to_string(),
+                Some(format!("`{file}` is already included above this file")),
+            ));
+            continue;
+        }
+        let name = path.display().to_string();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) => {
+                diag.push(error(err.to_string(), None));
+                continue;
+            }
+        };
+        included.files.push(path.clone());
+        let frag_src = SrcFile::new(&name, &content);
+        let fragment: FragmentOpt = match toml::from_str(&content) {
+            Ok(fragment) => fragment,
+            Err(err) => {
+                let (line, column) = match err.span() {
+                    Some(span) => frag_src.line_col(span.start),
+                    None => (0, 0),
+                };
+                let hint = match unknown_key(err.message()) {
+                    Some((_, hint)) => hint,
+                    None => {
+                        "included files only hold `include`, `template`, `section` and `instance`"
+                            .to_string()
+                    }
+                };
+                diag.push(error(
+                    format!(
+                        "invalid toml [line: {line}, column: {column}] => {}",
+                        err.message().trim_end()
+                    ),
+                    Some(hint),
+                ));
+                continue;
+            }
+        };
+
+        // NB: Nested includes come first
+        if let Some(include) = fragment.include.as_ref() {
+            load(include, Some(&frag_src), &sub_chain, included, diag);
+        }
+
+        let src = Some(&frag_src);
+        included.template_register = merge_map(
+            "register template",
+            std::mem::take(&mut included.template_register),
+            fragment.template.register,
+            src,
+            diag,
+        );
+        included.template_section = merge_map(
+            "section template",
+            std::mem::take(&mut included.template_section),
+            fragment.template.section,
+            src,
+            diag,
+        );
+        let section = fragment
+            .section
+            .into_iter()
+            .map(|(name, mut sec)| {
+                sec.get_mut().src = Some(frag_src.clone());
+                (name, sec)
+            })
+            .collect();
+        included.section = merge_map(
+            "section",
+            std::mem::take(&mut included.section),
+            section,
+            src,
+            diag,
+        );
+        included.instance.extend(
+            fragment
+                .instance
+                .into_iter()
+                .map(|(name, inst)| (name, inst, Some(frag_src.clone()))),
+        );
+    }
+}
+
+/// Append `next` entries after the `first` ones, duplicated names are reported and dropped
+/// NB: `src` is the file of the `next` entries
+fn merge_map<T>(
+    kind: &str,
+    mut first: IndexMap<String, Spanned<T>>,
+    next: IndexMap<String, Spanned<T>>,
+    src: Option<&Arc<SrcFile>>,
+    diag: &mut Diagnostics,
+) -> IndexMap<String, Spanned<T>> {
+    for (name, elem) in next {
+        if first.contains_key(&name) {
+            let ctx = SrcCtx::new(format!("{kind} `{name}`"), span_in(src, &elem));
+            diag.push(duplicate(kind, &name, &ctx));
+        } else {
+            first.insert(name, elem);
+        }
+    }
+    first
+}
+
+fn duplicate(kind: &str, name: &str, ctx: &SrcCtx) -> RegmapError {
+    RegmapError::DuplicateName {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        ctx: ctx.with_hint(format!(
+            "`{name}` is already defined (i.e. in this file or in an included one)"
+        )),
+    }
+}
+
+/// Build an element from its template and the instance overrides
+fn instantiate<T: serde::de::DeserializeOwned>(
+    templates: &IndexMap<String, Spanned<toml::Table>>,
+    inst: &InstanceOpt,
+    ctx: &SrcCtx,
+    diag: &mut Diagnostics,
+) -> Option<T> {
+    let error = |msg: String, hint: String| RegmapError::Template {
+        template: inst.template.clone(),
+        msg,
+        ctx: ctx.with_hint(hint),
+    };
+    let Some(template) = templates.get(&inst.template) else {
+        let available = templates.keys().cloned().collect::<Vec<_>>();
+        diag.push(error(
+            "unknown template".to_string(),
+            if available.is_empty() {
+                "no template of this kind is defined".to_string()
+            } else {
+                format!("available templates are [{}]", available.join(", "))
+            },
+        ));
+        return None;
+    };
+
+    let mut table = template.get_ref().clone();
+    merge(&mut table, inst.overrides.clone());
+    // NB: Spanned elements are only deserialized from a document -> Go through a string
+    let parsed = toml::to_string(&table)
+        .map_err(|err| err.to_string())
+        .and_then(|doc| toml::from_str::<T>(&doc).map_err(|err| err.message().to_string()));
+    match parsed {
+        Ok(elem) => Some(elem),
+        Err(msg) => {
+            let hint = match unknown_key(&msg) {
+                Some((_, hint)) => hint,
+                None => "complete the template or the instance overrides".to_string(),
+            };
+            diag.push(error(msg.trim_end().to_string(), hint));
+            None
+        }
+    }
+}
+
+/// Merge the overrides in a template, tables are merged recursively and other values replaced
+/// NB: A default value is an enum -> Always replaced
+fn merge(base: &mut toml::Table, overrides: toml::Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(value)) if key != "default" => {
+                merge(base, value)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Locate the elements built from a template on the instance
+fn respan_registers(
+    registers: IndexMap<String, Spanned<RegisterOpt>>,
+    span: &Range<usize>,
+) -> IndexMap<String, Spanned<RegisterOpt>> {
+    registers
+        .into_iter()
+        .map(|(name, reg)| {
+            let mut reg = reg.into_inner();
+            respan_fields(&mut reg, span);
+            (name, Spanned::new(span.clone(), reg))
+        })
+        .collect()
+}
+
+fn respan_fields(register: &mut RegisterOpt, span: &Range<usize>) {
+    register.field = register.field.take().map(|fields| {
+        fields
+            .into_iter()
+            .map(|(name, field)| (name, Spanned::new(span.clone(), field.into_inner())))
+            .collect()
+    });
+}
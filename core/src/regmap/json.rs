@@ -0,0 +1,271 @@
+//!
+//! Conversion of the elaborated register map toward the json interchange format
+//! The model itself is defined in `crate::model`, shared with the runtime.
+//!
+
+use std::collections::HashSet;
+
+use super::block::Block;
+use super::{DefaultVal, Field, Register, Regmap, RegmapError, Section, Slice};
+use crate::model::{
+    JsonBlock, JsonDefault, JsonDoc, JsonField, JsonFieldDefault, JsonRegister, JsonRegmap,
+    JsonSection, JsonSlice, SCHEMA_VERSION,
+};
+
+impl From<&DefaultVal> for JsonDefault {
+    fn from(dflt: &DefaultVal) -> Self {
+        match dflt {
+            DefaultVal::Cst(value) => Self::Const { value: *value },
+            DefaultVal::Param(name) => Self::Param { name: name.clone() },
+            DefaultVal::ParamsField { params, name_val } => Self::ParamsField {
+                params: params.clone(),
+                field: name_val
+                    .iter()
+                    .map(|(name, value)| JsonFieldDefault {
+                        name: name.clone(),
+                        value: value.clone(),
+                    })
+                    .collect(),
+            },
+        }
+    }
+}
+
+impl From<JsonDefault> for DefaultVal {
+    fn from(dflt: JsonDefault) -> Self {
+        match dflt {
+            JsonDefault::Const { value } => Self::Cst(value),
+            JsonDefault::Param { name } => Self::Param(name),
+            JsonDefault::ParamsField { params, field } => Self::ParamsField {
+                params,
+                name_val: field.into_iter().map(|f| (f.name, f.value)).collect(),
+            },
+        }
+    }
+}
+
+impl From<&Field> for JsonField {
+    fn from(field: &Field) -> Self {
+        Self {
+            name: field.name.clone(),
+            description: field.description.clone(),
+            size_b: field.size_b,
+            size_param: field.size_param.clone(),
+            width: field.width,
+            offset_b: field.offset_b,
+            default: field.default.as_ref().map(JsonDefault::from),
+            reset: field.reset,
+            reserved: field.reserved,
+        }
+    }
+}
+
+impl From<JsonField> for Field {
+    fn from(field: JsonField) -> Self {
+        Self {
+            name: field.name,
+            description: field.description,
+            size_b: field.size_b,
+            size_param: field.size_param,
+            width: field.width,
+            offset_b: field.offset_b,
+            default: field.default.map(DefaultVal::from),
+            reset: field.reset,
+            reserved: field.reserved,
+            span: None,
+        }
+    }
+}
+
+impl From<&Register> for JsonRegister {
+    fn from(reg: &Register) -> Self {
+        Self {
+            name: reg.name.clone(),
+            description: reg.description.clone(),
+            owner: reg.owner,
+            read_access: reg.read_access,
+            write_access: reg.write_access,
+            offset: reg.offset,
+            bytes_align: Some(reg.bytes_align),
+            default: JsonDefault::from(&reg.default),
+            reset: reg.reset,
+            reserved: reg.reserved,
+            field: reg.field.iter().flatten().map(JsonField::from).collect(),
+        }
+    }
+}
+
+impl From<JsonRegister> for Register {
+    fn from(reg: JsonRegister) -> Self {
+        Self {
+            name: reg.name,
+            description: reg.description,
+            owner: reg.owner,
+            read_access: reg.read_access,
+            write_access: reg.write_access,
+            offset: reg.offset,
+            // NB: Omitted by older documents, fallback to the word size once the regmap is known
+            bytes_align: reg.bytes_align.unwrap_or_default(),
+            default: reg.default.into(),
+            reset: reg.reset,
+            // NB: Register without field is stored without field list
+            field: (!reg.field.is_empty())
+                .then(|| reg.field.into_iter().map(Field::from).collect()),
+            reserved: reg.reserved,
+            span: None,
+        }
+    }
+}
+
+impl From<&Section> for JsonSection {
+    fn from(sec: &Section) -> Self {
+        Self {
+            name: sec.name.clone(),
+            description: sec.description.clone(),
+            offset: sec.offset,
+            bytes_align: sec.bytes_align,
+            range: sec.range,
+            reserved: sec.reserved,
+            register: sec.register.iter().map(JsonRegister::from).collect(),
+        }
+    }
+}
+
+impl From<JsonSection> for Section {
+    fn from(sec: JsonSection) -> Self {
+        Self {
+            name: sec.name,
+            description: sec.description,
+            offset: sec.offset,
+            bytes_align: sec.bytes_align,
+            range: sec.range,
+            register: sec.register.into_iter().map(Register::from).collect(),
+            reserved: sec.reserved,
+            span: None,
+        }
+    }
+}
+
+impl From<&Slice> for JsonSlice {
+    fn from(slice: &Slice) -> Self {
+        Self {
+            module_name: slice.module_name.clone(),
+            description: slice.description.clone(),
+            offset: slice.offset,
+            range: slice.range,
+            section: slice.section.clone(),
+            block: slice.block.clone(),
+        }
+    }
+}
+
+impl From<JsonSlice> for Slice {
+    fn from(slice: JsonSlice) -> Self {
+        Self {
+            module_name: slice.module_name,
+            description: slice.description,
+            offset: slice.offset,
+            range: slice.range,
+            section: slice.section,
+            block: slice.block,
+        }
+    }
+}
+
+impl From<&Regmap> for JsonRegmap {
+    fn from(regmap: &Regmap) -> Self {
+        let mut ext_pkg = regmap.ext_pkg.iter().cloned().collect::<Vec<_>>();
+        ext_pkg.sort();
+        Self {
+            module_name: regmap.module_name.clone(),
+            description: regmap.description.clone(),
+            word_size_b: regmap.word_size_b,
+            offset: regmap.offset,
+            range: regmap.range,
+            ext_pkg,
+            param: regmap
+                .param
+                .iter()
+                .map(|(name, val)| (name.clone(), *val))
+                .collect(),
+            section: regmap.section.iter().map(JsonSection::from).collect(),
+            block: regmap
+                .block
+                .iter()
+                .map(|blk| JsonBlock {
+                    name: blk.name().clone(),
+                    description: blk.description().clone(),
+                    file: blk.file().clone(),
+                    offset: *blk.offset(),
+                    range: *blk.range(),
+                    regmap: JsonRegmap::from(blk.regmap()),
+                })
+                .collect(),
+            slice: regmap.slice.iter().map(JsonSlice::from).collect(),
+        }
+    }
+}
+
+impl From<JsonRegmap> for Regmap {
+    fn from(regmap: JsonRegmap) -> Self {
+        let word_bytes = regmap.word_size_b.div_ceil(u8::BITS as usize);
+        let mut section = regmap
+            .section
+            .into_iter()
+            .map(Section::from)
+            .collect::<Vec<_>>();
+        for reg in section.iter_mut().flat_map(|sec| sec.register.iter_mut()) {
+            if reg.bytes_align == 0 {
+                reg.bytes_align = word_bytes;
+            }
+        }
+        Self {
+            module_name: regmap.module_name,
+            description: regmap.description,
+            word_size_b: regmap.word_size_b,
+            offset: regmap.offset,
+            range: regmap.range,
+            ext_pkg: regmap.ext_pkg.into_iter().collect::<HashSet<_>>(),
+            section,
+            block: regmap
+                .block
+                .into_iter()
+                .map(|blk| {
+                    Block::new(
+                        blk.name,
+                        blk.description,
+                        blk.file,
+                        blk.offset,
+                        blk.range,
+                        blk.regmap.into(),
+                    )
+                })
+                .collect(),
+            param: regmap.param.into_iter().collect(),
+            slice: regmap.slice.into_iter().map(Slice::from).collect(),
+            source: Vec::new(),
+        }
+    }
+}
+
+impl Regmap {
+    /// Serialize the regmap in the versioned json interchange format
+    pub fn to_json(&self) -> String {
+        let doc = JsonDoc {
+            schema_version: SCHEMA_VERSION,
+            regmap: JsonRegmap::from(self),
+        };
+        serde_json::to_string_pretty(&doc).expect("Regmap serialization can not fail")
+    }
+
+    /// Load a regmap from the json interchange format (i.e. output of the `json` generator)
+    /// NB: file is only used to give context in error message
+    pub fn from_json(file: &str, content: &str) -> Result<Self, RegmapError> {
+        JsonDoc::from_json_str(file, content).map(|doc| doc.regmap.into())
+    }
+
+    /// Read a regmap from a json interchange file
+    pub fn read_json(file: &str) -> Result<Self, RegmapError> {
+        JsonDoc::read_from(file).map(|doc| doc.regmap.into())
+    }
+}
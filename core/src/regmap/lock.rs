@@ -0,0 +1,199 @@
+//!
+//! Offset lock file
+//! Record the address assigned to each section and register, similar to Cargo.lock.
+//! With a lock, locked elements keep their address whatever the edits in the toml definition and
+//! new elements are placed in the free holes. A definition that would move a locked address is
+//! rejected.
+//!
+//! Keys are the expanded names (i.e. `section` and `section::register`), offsets are absolute.
+//! Elements of child blocks are prefixed by the block instance name (i.e. `block::section`).
+//!
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::span::SrcFile;
+use super::{Regmap, RegmapError};
+
+/// Version of the lock file format
+pub const LOCK_VERSION: usize = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegmapLock {
+    pub version: usize,
+    #[serde(default)]
+    pub section: IndexMap<String, usize>,
+    #[serde(default)]
+    pub register: IndexMap<String, usize>,
+    #[serde(default)]
+    pub block: IndexMap<String, usize>,
+}
+
+impl RegmapLock {
+    /// Lock all the addresses of an elaborated regmap
+    pub fn from_regmap(regmap: &Regmap) -> Self {
+        let mut lock = Self {
+            version: LOCK_VERSION,
+            ..Default::default()
+        };
+        lock.insert_regmap("", regmap);
+        lock
+    }
+
+    /// Lock the addresses of a regmap and recursively of its blocks
+    fn insert_regmap(&mut self, prefix: &str, regmap: &Regmap) {
+        for sec in regmap.section().iter() {
+            self.section
+                .insert(format!("{prefix}{}", sec.name()), *sec.offset());
+            for reg in sec.register().iter() {
+                self.register.insert(
+                    format!("{prefix}{}::{}", sec.name(), reg.name()),
+                    *reg.offset(),
+                );
+            }
+        }
+        for blk in regmap.block().iter() {
+            self.block
+                .insert(format!("{prefix}{}", blk.name()), *blk.offset());
+            self.insert_regmap(&format!("{prefix}{}::", blk.name()), blk.regmap());
+        }
+    }
+
+    /// Entries of a child block with the block prefix removed
+    pub fn scoped(&self, block: &str) -> Self {
+        let prefix = format!("{block}::");
+        let strip = |entries: &IndexMap<String, usize>| {
+            entries
+                .iter()
+                .filter_map(|(key, offset)| {
+                    key.strip_prefix(&prefix)
+                        .map(|key| (key.to_string(), *offset))
+                })
+                .collect()
+        };
+        Self {
+            version: self.version,
+            section: strip(&self.section),
+            register: strip(&self.register),
+            block: strip(&self.block),
+        }
+    }
+
+    /// Read a lock file
+    pub fn read_from(file: &str) -> Result<Self, RegmapError> {
+        let content = std::fs::read_to_string(file).map_err(|err| RegmapError::Io {
+            file: file.to_string(),
+            msg: err.to_string(),
+        })?;
+        Self::from_toml_str(file, &content)
+    }
+
+    /// Parse a lock from a string
+    /// NB: file is only used to give context in error message
+    pub fn from_toml_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        let lock: Self = toml::from_str(content).map_err(|err| {
+            let (line, column) = match err.span() {
+                Some(span) => SrcFile::new(file, content).line_col(span.start),
+                None => (0, 0),
+            };
+            RegmapError::TomlSyntax {
+                file: file.to_string(),
+                line,
+                column,
+                msg: err.message().trim_end().to_string(),
+            }
+        })?;
+        if lock.version != LOCK_VERSION {
+            return Err(RegmapError::LockVersion {
+                file: file.to_string(),
+                found: lock.version,
+                supported: LOCK_VERSION,
+            });
+        }
+        Ok(lock)
+    }
+
+    /// Serialize the lock in toml
+    /// Offsets are written in hexadecimal for easy review
+    pub fn to_toml(&self) -> String {
+        let mut doc = DocumentMut::new();
+        doc["version"] = toml_edit::value(self.version as i64);
+        if let Some(mut key) = doc.as_table_mut().key_mut("version") {
+            key.leaf_decor_mut().set_prefix(
+                "# This file is automatically generated by hw_regmap.\n# It is not intended for manual editing.\n",
+            );
+        }
+        for (name, entries) in [
+            ("section", &self.section),
+            ("register", &self.register),
+            ("block", &self.block),
+        ] {
+            // NB: Block table is only written for hierarchical regmaps
+            if name == "block" && entries.is_empty() {
+                continue;
+            }
+            let mut table = Table::new();
+            for (key, offset) in entries.iter() {
+                table.insert(
+                    key,
+                    Item::Value(
+                        format!("0x{offset:x}")
+                            .parse()
+                            .expect("Hexadecimal literal is a valid TOML value"),
+                    ),
+                );
+            }
+            doc[name] = Item::Table(table);
+        }
+        super::format::fmt_document(&mut doc);
+        doc.to_string()
+    }
+
+    /// Write the lock file
+    pub fn write(&self, file: &str) -> Result<(), RegmapError> {
+        std::fs::write(file, self.to_toml()).map_err(|err| RegmapError::Io {
+            file: file.to_string(),
+            msg: err.to_string(),
+        })
+    }
+
+    pub fn section_offset(&self, name: &str) -> Option<usize> {
+        self.section.get(name).copied()
+    }
+
+    pub fn register_offset(&self, section: &str, name: &str) -> Option<usize> {
+        self.register.get(&format!("{section}::{name}")).copied()
+    }
+
+    pub fn block_offset(&self, name: &str) -> Option<usize> {
+        self.block.get(name).copied()
+    }
+
+    /// Entries added and removed to get `other`
+    pub fn changes(&self, other: &Self) -> (Vec<String>, Vec<String>) {
+        let keys = |lock: &Self| {
+            lock.section
+                .keys()
+                .chain(lock.register.keys())
+                .chain(lock.block.keys())
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        let (old, new) = (keys(self), keys(other));
+        let added = new.iter().filter(|k| !old.contains(k)).cloned().collect();
+        let removed = old.iter().filter(|k| !new.contains(k)).cloned().collect();
+        (added, removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsupported_version() {
+        let err = RegmapLock::from_toml_str("test.lock", "version = 2").unwrap_err();
+        assert_eq!(err.kind(), "LockVersion");
+    }
+}
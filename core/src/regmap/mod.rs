@@ -0,0 +1,1550 @@
+pub mod alloc;
+pub mod block;
+pub mod diag;
+pub mod expr;
+pub mod feature;
+pub mod format;
+pub mod include;
+pub mod json;
+pub mod lock;
+pub mod param;
+pub mod parser;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use indexmap::map::Iter;
+
+use alloc::{Allocator, Strategy};
+use block::Block;
+use diag::Diagnostics;
+use expr::Expr;
+use getset::{Getters, MutGetters};
+use indexmap::IndexMap;
+use parser::{Owner, ReadAccess, Reserved, WriteAccess};
+use serde::{Deserialize, Serialize};
+use span::{SrcCtx, SrcFile, SrcSpan};
+use toml::Spanned;
+
+// NB: Runtime parts are re-exported to keep the generator paths
+pub use crate::error::RegmapError;
+pub use crate::model::DefaultVal;
+pub use crate::span;
+
+impl DefaultVal {
+    /// Check that a constant default fits in the given width
+    /// NB: Parameters value is unknown at this point, they are checked at RTL elaboration
+    fn check_width(&self, width: usize, ctx: &SrcCtx, diag: &mut Diagnostics) {
+        if let Self::Cst(value) = self {
+            if width < usize::BITS as usize && (*value >> width) != 0 {
+                diag.push(RegmapError::DfltOverflow {
+                    value: *value,
+                    width,
+                    ctx: ctx.with_hint(format!(
+                        "maximum value for {width} bits is 0x{:x}",
+                        (1_usize << width) - 1
+                    )),
+                });
+            }
+        }
+    }
+}
+
+/// Elaboration options
+/// Default is a sequential allocation without lock
+#[derive(Debug, Clone, Default)]
+pub struct ElabOpt {
+    /// Frozen addresses: locked elements keep their address and new ones are placed in free holes
+    pub lock: Option<lock::RegmapLock>,
+    /// Allocation strategy of the elements without explicit offset
+    pub strategy: Strategy,
+    /// Values of the RTL parameters used as default value
+    pub params: param::ParamSet,
+    /// Enabled features, elements with an `if` predicate are kept only when it holds
+    pub features: Vec<String>,
+    /// Build variant, i.e. named set of features of the `[variant]` table
+    pub variant: Option<String>,
+    /// Keep the space of the excluded elements reserved, thus the layout doesn't depend on the
+    /// features
+    pub reserve_excluded: bool,
+}
+
+impl ElabOpt {
+    /// Strategy used to fill the holes, None for sequential allocation
+    /// NB: With a lock, new elements are always placed in holes (first fit by default)
+    fn fit_strategy(&self) -> Option<Strategy> {
+        match (self.strategy, self.lock.as_ref()) {
+            (Strategy::Sequential, None) => None,
+            (Strategy::Sequential, Some(_)) => Some(Strategy::FirstFit),
+            (strategy, _) => Some(strategy),
+        }
+    }
+}
+
+/// Utility function to compute aligned offset
+fn align_on(bytes_align: usize, val: usize) -> usize {
+    let remainder = val % bytes_align;
+
+    if 0 != remainder {
+        val + bytes_align - remainder
+    } else {
+        val
+    }
+}
+
+/// Check that names are unique at a given hierarchy level
+/// Names are checked after expansion (i.e. with duplicate suffix)
+fn check_unique<'a>(
+    kind: &str,
+    items: impl Iterator<Item = (&'a String, &'a Option<SrcSpan>)>,
+    diag: &mut Diagnostics,
+) {
+    let mut seen: HashMap<&String, &Option<SrcSpan>> = HashMap::new();
+    for (name, span) in items {
+        if let Some(first) = seen.get(name) {
+            let ctx = SrcCtx::new(format!("{kind} `{name}`"), span.clone());
+            let hint = match first {
+                Some(first) => format!("`{name}` is already defined at {first}"),
+                None => format!("`{name}` is already defined"),
+            };
+            diag.push(RegmapError::DuplicateName {
+                kind: kind.to_string(),
+                name: name.clone(),
+                ctx: ctx.with_hint(hint),
+            });
+        } else {
+            seen.insert(name, span);
+        }
+    }
+}
+
+/// Check that the value given to a parameter used as default fits in the given width
+/// NB: Without value, parameters are checked at RTL elaboration
+fn check_param_width(
+    dflt: &DefaultVal,
+    reset: Option<usize>,
+    width: usize,
+    ctx: &SrcCtx,
+    diag: &mut Diagnostics,
+) {
+    if let (DefaultVal::Param(param), Some(value)) = (dflt, reset) {
+        if width < usize::BITS as usize && (value >> width) != 0 {
+            diag.push(RegmapError::DfltOverflow {
+                value,
+                width,
+                ctx: ctx.with_hint(format!(
+                    "parameter `{param}` is set to 0x{value:x}, maximum value for {width} bits is 0x{:x}",
+                    (1_usize << width) - 1
+                )),
+            });
+        }
+    }
+}
+
+/// Check that read-as-zero elements have a null default value
+fn check_zero_default(
+    policy: Reserved,
+    dflt: Option<&DefaultVal>,
+    ctx: &SrcCtx,
+    diag: &mut Diagnostics,
+) {
+    let non_zero = dflt.filter(|dflt| **dflt != DefaultVal::Cst(0));
+    if let (Reserved::ReadAsZero, Some(dflt)) = (policy, non_zero) {
+        diag.push(RegmapError::Reserved {
+            policy,
+            msg: format!("default value {dflt:?} isn't 0"),
+            ctx: ctx
+                .with_hint("remove the default value or use the WriteIgnored policy".to_string()),
+        });
+    }
+}
+
+/// Locate a spanned element in its source file
+fn span_in<T>(src: Option<&Arc<SrcFile>>, elem: &Spanned<T>) -> Option<SrcSpan> {
+    src.map(|src| SrcSpan::new(src, elem.span()))
+}
+
+#[derive(Debug, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Field {
+    name: String,
+    description: String,
+    size_b: usize,
+    /// RTL parameter giving the actual width, size_b is then the maximum width
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size_param: Option<String>,
+    /// Concrete width of a parameterized field (i.e. with the parameters values)
+    #[getset(skip)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    width: Option<usize>,
+    offset_b: usize,
+    default: Option<DefaultVal>,
+    /// Concrete default value (i.e. with the parameters values)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reset: Option<usize>,
+    /// Reserved bits, no port is generated for them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reserved: Option<Reserved>,
+    #[serde(skip)]
+    span: Option<SrcSpan>,
+}
+
+impl Field {
+    pub fn from_opt(
+        fields: &mut Iter<'_, String, Spanned<parser::FieldOpt>>,
+        word_size: usize,
+        src: Option<&Arc<SrcFile>>,
+        params: &param::ParamSet,
+        diag: &mut Diagnostics,
+    ) -> Vec<Self> {
+        let mut expanded_field = Vec::with_capacity(fields.len());
+        let mut nxt_offset = 0;
+        for (name, spanned) in fields {
+            let field = spanned.get_ref();
+            let span = span_in(src, spanned);
+            let ctx = SrcCtx::new(format!("field `{name}`"), span.clone());
+            let size_b = field.size_b.value();
+            let offset_b = match field.offset_b.as_ref() {
+                Some(ofst) => ofst.value(),
+                None => nxt_offset,
+            };
+            // NB: Excluded fields only reserve their bits -> Read as zero
+            let (reserved, default) = if field.excluded {
+                (Some(Reserved::ReadAsZero), None)
+            } else {
+                (
+                    field.reserved,
+                    field.default.as_ref().map(parser::DefaultOpt::value),
+                )
+            };
+            if let Some(policy) = reserved {
+                if field.size_param.is_some() {
+                    diag.push(RegmapError::Reserved {
+                        policy,
+                        msg: "reserved bits couldn't be sized by a parameter".to_string(),
+                        ctx: ctx.with_hint("remove size_param".to_string()),
+                    });
+                }
+                check_zero_default(policy, default.as_ref(), &ctx, diag);
+            }
+
+            let word_b = word_size * u8::BITS as usize;
+            if (offset_b + size_b) > word_b {
+                diag.push(RegmapError::WordBoundary {
+                    word_b,
+                    field_offset: offset_b,
+                    field_b: size_b,
+                    ctx: ctx.with_hint(format!(
+                        "field must end before bit {word_b} (i.e. offset_b + size_b <= {word_b})"
+                    )),
+                });
+                // Drop the field to keep checking the others
+                continue;
+            }
+
+            // NB: Layout always relies on size_b, parameter only narrows the used bits
+            let width = field.size_param.as_ref().and_then(|param| {
+                let value = params.get(param)?;
+                if value == 0 || value > size_b {
+                    diag.push(RegmapError::SizeParam {
+                        param: param.clone(),
+                        value,
+                        max_b: size_b,
+                        ctx: ctx.with_hint(format!(
+                            "parameter `{param}` must be in [1, {size_b}] (i.e. size_b is the maximum width)"
+                        )),
+                    });
+                    None
+                } else {
+                    Some(value)
+                }
+            });
+
+            let reset = default.as_ref().and_then(|dflt| params.resolve(dflt));
+            if let Some(dflt) = default.as_ref() {
+                let used_b = width.unwrap_or(size_b);
+                dflt.check_width(used_b, &ctx, diag);
+                check_param_width(dflt, reset, used_b, &ctx, diag);
+            }
+
+            nxt_offset = offset_b + size_b;
+            expanded_field.push(Self {
+                name: name.clone(),
+                description: field.description.clone(),
+                size_b,
+                size_param: field.size_param.clone(),
+                width,
+                offset_b,
+                default,
+                reset,
+                reserved,
+                span,
+            });
+        }
+        // Sort by offset_b
+        expanded_field.sort_by_key(|a| a.offset_b);
+
+        check_unique(
+            "field",
+            expanded_field.iter().map(|f| (&f.name, &f.span)),
+            diag,
+        );
+
+        // Check that fields don't overlap
+        // NB: Fields are sorted, only neighbours have to be checked
+        for pair in expanded_field.windows(2) {
+            let (prev, cur) = (&pair[0], &pair[1]);
+            if prev.offset_b + prev.size_b > cur.offset_b {
+                let ctx = SrcCtx::new(format!("field `{}`", cur.name), cur.span.clone());
+                diag.push(RegmapError::FieldOverlap {
+                    field: cur.name.clone(),
+                    field_offset: cur.offset_b,
+                    field_b: cur.size_b,
+                    other: prev.name.clone(),
+                    other_offset: prev.offset_b,
+                    other_b: prev.size_b,
+                    ctx: ctx.with_hint(format!(
+                        "first free bit after `{}` is {}",
+                        prev.name,
+                        prev.offset_b + prev.size_b
+                    )),
+                });
+            }
+        }
+        expanded_field
+    }
+
+    /// Actual width of the field
+    /// NB: Parameterized field without parameter value fallback to its maximum width
+    pub fn width(&self) -> usize {
+        self.width.unwrap_or(self.size_b)
+    }
+
+    pub fn get_default(fields: &[Self], diag: &mut Diagnostics) -> Option<DefaultVal> {
+        let field_with_dflt = fields
+            .iter()
+            .filter(|field| field.default.is_some())
+            .collect::<Vec<_>>();
+
+        if field_with_dflt.is_empty() {
+            None
+        } else {
+            let mut params = Vec::new();
+            let mut name_val = Vec::new();
+
+            for field in fields.iter() {
+                match field.default.as_ref() {
+                    Some(DefaultVal::Param(p)) => {
+                        // Expose parameters at interface and update name_val
+                        params.push(p.clone());
+                        name_val.push((field.name.clone(), p.clone()));
+                    }
+                    Some(DefaultVal::Cst(val)) => {
+                        // Update name_val only
+                        name_val.push((field.name.clone(), format!("'h{val:x}")));
+                    }
+                    Some(dflt @ DefaultVal::ParamsField { .. }) => {
+                        diag.push(RegmapError::DfltInvalid {
+                            dflt: dflt.clone(),
+                            ctx: SrcCtx::new(format!("field `{}`", field.name), field.span.clone())
+                                .with_hint("use {Cst=<val>} or {Param=\"<name>\"}".to_string()),
+                        });
+                        // Use 0 value to keep checking
+                        name_val.push((field.name.clone(), "'h0".to_string()));
+                    }
+                    None => {
+                        // Update name_val only with 0 value
+                        name_val.push((field.name.clone(), "'h0".to_string()));
+                    }
+                };
+            }
+            Some(DefaultVal::ParamsField { params, name_val })
+        }
+    }
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "      name: {}", self.name)?;
+        writeln!(f, "      description: {}", self.description)?;
+        writeln!(
+            f,
+            "      size_b: {}, offset_b: {}, default: {:?}",
+            self.size_b, self.offset_b, self.default
+        )?;
+        if let Some(param) = self.size_param.as_ref() {
+            writeln!(f, "      size_param: {param}")?;
+        }
+        if let Some(policy) = self.reserved {
+            writeln!(f, "      reserved: {policy}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Register {
+    name: String,
+    description: String,
+    owner: Owner,
+    read_access: ReadAccess,
+    write_access: WriteAccess,
+    offset: usize,
+    /// Alignment of the offset in bytes (i.e. word size when not requested)
+    bytes_align: usize,
+    default: DefaultVal,
+    /// Concrete default value (i.e. with the parameters values)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reset: Option<usize>,
+    field: Option<Vec<Field>>,
+    /// Reserved register, no port is generated for it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reserved: Option<Reserved>,
+    #[serde(skip)]
+    span: Option<SrcSpan>,
+}
+
+/// Element waiting for its address
+/// Only the first duplicated instance carries the user requested offset
+struct Instance<T> {
+    elem: T,
+    bytes_align: usize,
+    request: Option<usize>,
+    ctx: SrcCtx,
+}
+
+impl Register {
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_opt(
+        regs: &mut Iter<'_, String, Spanned<parser::RegisterOpt>>,
+        section: &str,
+        section_offset: usize,
+        word_size: usize,
+        src: Option<&Arc<SrcFile>>,
+        elab: &ElabOpt,
+        diag: &mut Diagnostics,
+    ) -> Vec<Self> {
+        let word_bytes = word_size / std::mem::size_of::<u8>();
+        let mut instances = Vec::with_capacity(regs.len());
+
+        for (name, spanned) in regs {
+            let register = spanned.get_ref();
+            let span = span_in(src, spanned);
+            let ctx = SrcCtx::new(format!("register `{name}`"), span.clone());
+            // NB: Excluded registers only reserve their space -> Read as zero
+            let reserved = if register.excluded {
+                Some(Reserved::ReadAsZero)
+            } else {
+                register.reserved
+            };
+
+            // Retrieved access rights, reserved registers imply them
+            let keys = (register.owner, register.read_access, register.write_access);
+            let (owner, read_access, write_access) = match (reserved, keys) {
+                (Some(policy), _) if register.excluded => policy.access(),
+                (Some(policy), (None, None, None)) => policy.access(),
+                (Some(policy), _) => {
+                    diag.push(RegmapError::Reserved {
+                        policy,
+                        msg: "access rights are implied by the policy".to_string(),
+                        ctx: ctx
+                            .with_hint("remove owner, read_access and write_access".to_string()),
+                    });
+                    policy.access()
+                }
+                (None, (Some(owner), Some(rd), Some(wr))) => (owner, rd, wr),
+                (None, (owner, rd, wr)) => {
+                    let missing = [
+                        ("owner", owner.is_none()),
+                        ("read_access", rd.is_none()),
+                        ("write_access", wr.is_none()),
+                    ];
+                    for (key, _) in missing.iter().filter(|(_, none)| *none) {
+                        diag.push(RegmapError::MissingKey {
+                            key: key.to_string(),
+                            ctx: ctx.with_hint(
+                                "only reserved registers imply their access rights".to_string(),
+                            ),
+                        });
+                    }
+                    // Drop the register to keep checking the others
+                    continue;
+                }
+            };
+
+            // Check correctness of the mode
+            match (owner, read_access, write_access) {
+                (Owner::Parameter, ReadAccess::Read, WriteAccess::None) => {}
+                (Owner::Parameter, _rd, _wr) => diag.push(RegmapError::Access {
+                        owner,
+                        rd: read_access,
+                        wr: write_access,
+                        ctx: ctx.with_hint(
+                            "Parameter register must be read_access=\"Read\", write_access=\"None\""
+                                .to_string(),
+                        ),
+                    }),
+                (Owner::User, _rd, _wr) => {}
+                (Owner::Kernel, _rd, WriteAccess::Write) => diag.push(RegmapError::Access {
+                        owner,
+                        rd: read_access,
+                        wr: write_access,
+                        ctx: ctx.with_hint(
+                            "Kernel register couldn't be written by the user, use write_access=\"WriteNotify\" or \"None\""
+                                .to_string(),
+                        ),
+                    }),
+                (Owner::Kernel, _rd, _wr) => {}
+            }
+
+            // Extract required alignment
+            // Subword alignment is not supported
+            let bytes_align = match register.bytes_align.as_ref().map(Expr::value) {
+                Some(align) => {
+                    if (align % word_bytes) != 0 {
+                        diag.push(RegmapError::ByteAlign {
+                            word_align: word_bytes,
+                            request_align: align,
+                            ctx: ctx.with_hint(format!(
+                                "bytes_align must be a multiple of the word size (i.e. 0x{word_bytes:x})"
+                            )),
+                        });
+                        word_bytes
+                    } else {
+                        align
+                    }
+                }
+                None => word_bytes,
+            };
+
+            // Compute requested offset with alignment
+            let request = register
+                .offset
+                .as_ref()
+                .map(|ofst| align_on(bytes_align, ofst.value() + section_offset));
+
+            // Expand inner
+            // NB: Content of excluded registers is dropped, reserved ones couldn't have fields
+            let expand_field = match (register.field.as_ref(), reserved) {
+                (Some(_), _) if register.excluded => None,
+                (Some(_), Some(policy)) => {
+                    diag.push(RegmapError::Reserved {
+                        policy,
+                        msg: "reserved register couldn't have fields".to_string(),
+                        ctx: ctx.with_hint(
+                            "reserve the bit ranges with the `reserved` field property instead"
+                                .to_string(),
+                        ),
+                    });
+                    None
+                }
+                (Some(fields), None) => {
+                    let concrete_fields =
+                        Field::from_opt(&mut fields.iter(), word_size, src, &elab.params, diag);
+                    Some(concrete_fields)
+                }
+                (None, _) => None,
+            };
+
+            // Expand default
+            let default_opt = match register.excluded {
+                true => None,
+                false => register.default.as_ref().map(parser::DefaultOpt::value),
+            };
+            let default = match default_opt {
+                Some(dflt) => match expand_field.as_ref() {
+                    Some(field) => match Field::get_default(field, diag) {
+                        Some(_dflt) => {
+                            diag.push(RegmapError::DfltOverride {
+                                ctx: ctx.with_hint(
+                                    "remove either the register default or the fields default"
+                                        .to_string(),
+                                ),
+                            });
+                            DefaultVal::Cst(0)
+                        }
+                        None => match dflt {
+                            DefaultVal::ParamsField { .. } => {
+                                diag.push(RegmapError::DfltInvalid {
+                                    dflt,
+                                    ctx: ctx.with_hint(
+                                        "use {Cst=<val>} or {Param=\"<name>\"}".to_string(),
+                                    ),
+                                });
+                                DefaultVal::Cst(0)
+                            }
+                            _ => dflt,
+                        },
+                    },
+                    None => dflt,
+                },
+                None => match expand_field.as_ref() {
+                    Some(field) => match Field::get_default(field, diag) {
+                        Some(dflt) => dflt,
+                        None => DefaultVal::Cst(0),
+                    },
+                    None => DefaultVal::Cst(0),
+                },
+            };
+
+            if let Some(policy) = reserved {
+                check_zero_default(policy, Some(&default), &ctx, diag);
+            }
+            default.check_width(word_size * u8::BITS as usize, &ctx, diag);
+            let reset = match &default {
+                // NB: Fields without default are reset to 0
+                DefaultVal::ParamsField { .. } => {
+                    expand_field.iter().flatten().try_fold(0, |acc, f| {
+                        match (f.default.as_ref(), f.reset) {
+                            (None, _) => Some(acc),
+                            (Some(_), Some(val)) => Some(acc | (val << f.offset_b)),
+                            (Some(_), None) => None,
+                        }
+                    })
+                }
+                dflt => elab.params.resolve(dflt),
+            };
+            check_param_width(&default, reset, word_size * u8::BITS as usize, &ctx, diag);
+
+            // Build register instance
+            // NB: Offset is computed once all the registers are known
+            let reg = Self {
+                name: name.clone(),
+                description: register.description.clone(),
+                owner,
+                read_access,
+                write_access,
+                offset: 0,
+                bytes_align,
+                default,
+                reset,
+                field: expand_field,
+                reserved,
+                span,
+            };
+
+            // Handle duplication
+            // -> No duplication is 1 iteration without name extension
+            // NB: Duplication always have automatically computed offset
+            let duplicate = register.duplicate.clone().unwrap_or(vec![String::new()]);
+            duplicate.iter().enumerate().for_each(|(i, s)| {
+                let mut elem = reg.clone();
+                elem.name = format!("{}{}", name, s);
+                instances.push(Instance {
+                    elem,
+                    bytes_align,
+                    request: if i == 0 { request } else { None },
+                    ctx: ctx.clone(),
+                });
+            });
+        }
+
+        match elab.fit_strategy() {
+            // Sequential allocation: each register is placed after the previous one
+            None => {
+                let mut auto_offset = section_offset;
+                for inst in instances.iter_mut() {
+                    let offset = match inst.request {
+                        Some(request) if request < auto_offset => {
+                            diag.push(RegmapError::Offset {
+                                min_offset: auto_offset,
+                                request_offset: request,
+                                ctx: inst.ctx.with_hint(format!(
+                                    "next free offset is 0x{:x} (i.e. 0x{auto_offset:x} absolute)",
+                                    auto_offset - section_offset
+                                )),
+                            });
+                            // Use next free offset to keep checking
+                            align_on(inst.bytes_align, auto_offset)
+                        }
+                        Some(request) => request,
+                        None => align_on(inst.bytes_align, auto_offset),
+                    };
+                    inst.elem.offset = offset;
+                    // Update next usable offset
+                    auto_offset = offset + word_bytes;
+                }
+            }
+            // Fit allocation: locked and explicit offsets are fixed, others fill the free holes
+            Some(strategy) => {
+                let mut allocator = Allocator::new();
+                let mut pending = Vec::new();
+                for (i, inst) in instances.iter_mut().enumerate() {
+                    let locked = elab
+                        .lock
+                        .as_ref()
+                        .and_then(|lock| lock.register_offset(section, &inst.elem.name));
+                    let fixed = match (locked, inst.request) {
+                        (Some(locked), Some(request)) if locked != request => {
+                            diag.push(RegmapError::LockedOffset {
+                                kind: "register".to_string(),
+                                name: format!("{section}::{}", inst.elem.name),
+                                locked,
+                                request,
+                                ctx: inst.ctx.with_hint(format!(
+                                    "address is frozen in the lock file, restore offset 0x{:x} or remove the lock entry",
+                                    locked.saturating_sub(section_offset)
+                                )),
+                            });
+                            Some(locked)
+                        }
+                        (locked, request) => locked.or(request),
+                    };
+                    match fixed {
+                        Some(offset) => {
+                            if offset < section_offset {
+                                diag.push(RegmapError::Offset {
+                                    min_offset: section_offset,
+                                    request_offset: offset,
+                                    ctx: inst.ctx.with_hint(
+                                        "register must be placed after its section offset"
+                                            .to_string(),
+                                    ),
+                                });
+                            }
+                            inst.elem.offset = offset;
+                            allocator.claim(
+                                "register",
+                                &inst.elem.name,
+                                offset..offset + word_bytes,
+                                &inst.ctx,
+                                diag,
+                            );
+                        }
+                        None => pending.push(i),
+                    }
+                }
+                for i in pending {
+                    let inst = &mut instances[i];
+                    let offset =
+                        allocator.place(strategy, word_bytes, inst.bytes_align, section_offset);
+                    inst.elem.offset = offset;
+                    allocator.reserve(offset..offset + word_bytes, &inst.elem.name);
+                }
+            }
+        }
+
+        let mut expanded_register = instances
+            .into_iter()
+            .map(|inst| inst.elem)
+            .collect::<Vec<_>>();
+        // Sort by offset
+        expanded_register.sort_by_key(|a| a.offset);
+
+        check_unique(
+            "register",
+            expanded_register.iter().map(|r| (&r.name, &r.span)),
+            diag,
+        );
+        expanded_register
+    }
+
+    /// Parameters sizing the fields, with the maximum width of their field
+    pub fn size_params(&self) -> Vec<(String, usize)> {
+        self.field
+            .iter()
+            .flatten()
+            .filter_map(|f| f.size_param.clone().map(|p| (p, f.size_b)))
+            .collect()
+    }
+
+    /// RTL parameters used by the register (i.e. default value and field widths)
+    pub fn params_list(&self) -> Vec<String> {
+        let mut params = self.default.params_list();
+        for (p, _) in self.size_params() {
+            if !params.contains(&p) {
+                params.push(p);
+            }
+        }
+        params
+    }
+}
+
+impl std::fmt::Display for Register {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "    name: {}", self.name)?;
+        writeln!(f, "    description: {}", self.description)?;
+        writeln!(
+            f,
+            "    Access: {:?}, {:?}, {:?}, offset: 0x{:x}, default: {:?}",
+            self.owner, self.read_access, self.write_access, self.offset, self.default
+        )?;
+        if let Some(fields) = self.field.as_ref() {
+            write!(f, "    Field: [")?;
+            for field in fields.iter() {
+                write!(f, "\n[{field}]")?;
+            }
+            writeln!(f, "    ]")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Section {
+    name: String,
+    description: String,
+    offset: usize,
+    bytes_align: usize,
+    range: usize,
+    register: Vec<Register>,
+    /// Reserved address range, such section has no register
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reserved: Option<Reserved>,
+    #[serde(skip)]
+    span: Option<SrcSpan>,
+}
+
+impl Section {
+    pub fn from_opt(
+        sections: &mut Iter<'_, String, Spanned<parser::SectionOpt>>,
+        regmap_offset: usize,
+        word_bytes: usize,
+        src: Option<&Arc<SrcFile>>,
+        elab: &ElabOpt,
+        diag: &mut Diagnostics,
+    ) -> Vec<Self> {
+        let mut instances = Vec::with_capacity(sections.len());
+
+        for (name, spanned) in sections {
+            let section = spanned.get_ref();
+            let span = span_in(section.src.as_ref().or(src), spanned);
+            let ctx = SrcCtx::new(format!("section `{name}`"), span.clone());
+            // Reserved address range only has a range, its content is neither read nor stored
+            if let (Some(policy), false) = (section.reserved, section.excluded) {
+                let error = |msg: &str, hint: &str| RegmapError::Reserved {
+                    policy,
+                    msg: msg.to_string(),
+                    ctx: ctx.with_hint(hint.to_string()),
+                };
+                if policy == Reserved::PreserveOnWrite {
+                    diag.push(error(
+                        "address range couldn't be stored",
+                        "use ReadAsZero or WriteIgnored, or reserved registers",
+                    ));
+                }
+                if section
+                    .range
+                    .as_ref()
+                    .is_none_or(|range| range.value() == 0)
+                {
+                    diag.push(error(
+                        "missing or empty range",
+                        "give the size of the reserved range",
+                    ));
+                }
+                if !section.register.is_empty() {
+                    diag.push(error(
+                        "reserved section couldn't have registers",
+                        "reserve the registers with the `reserved` register property instead",
+                    ));
+                }
+            }
+            // Extract required alignment
+            // Subword alignment is not supported
+            let bytes_align = match section.bytes_align.as_ref().map(Expr::value) {
+                Some(align) => {
+                    if (align % word_bytes) != 0 {
+                        diag.push(RegmapError::ByteAlign {
+                            word_align: word_bytes,
+                            request_align: align,
+                            ctx: ctx.with_hint(format!(
+                                "bytes_align must be a multiple of the word size (i.e. 0x{word_bytes:x})"
+                            )),
+                        });
+                        word_bytes
+                    } else {
+                        align
+                    }
+                }
+                None => word_bytes,
+            };
+
+            // Compute requested offset with alignment
+            // TODO should we force alignment when offset specified by user
+            let request = section
+                .offset
+                .as_ref()
+                .map(|ofst| align_on(bytes_align, ofst.value() + regmap_offset));
+
+            // Handle duplication
+            // -> No duplication is 1iteration without name extension
+            for (i, s) in section
+                .duplicate
+                .clone()
+                .unwrap_or(vec![String::new()])
+                .iter()
+                .enumerate()
+            {
+                instances.push(Instance {
+                    elem: (format!("{}{}", name, s), section, span.clone(), i == 0),
+                    bytes_align,
+                    request: if i == 0 { request } else { None },
+                    ctx: ctx.clone(),
+                });
+            }
+        }
+
+        // Elaborate a section instance at the given offset
+        // NB: Duplicated instances have the same errors as the first one -> Not reported twice
+        let elaborate = |inst: &Instance<(String, &parser::SectionOpt, Option<SrcSpan>, bool)>,
+                         sec_offset: usize,
+                         diag: &mut Diagnostics| {
+            let (name, section, span, first) = &inst.elem;
+            let mut scratch = Diagnostics::new();
+            let diag = if *first { diag } else { &mut scratch };
+
+            // Expand inner register
+            let mut register = Register::from_opt(
+                &mut section.register.iter(),
+                name,
+                sec_offset,
+                word_bytes,
+                section.src.as_ref().or(src),
+                elab,
+                diag,
+            );
+
+            // Check range
+            let real_range = register
+                .iter()
+                .map(|reg| reg.offset + word_bytes)
+                .max()
+                .unwrap_or(sec_offset)
+                - sec_offset;
+
+            let range = if let Some(request_range) = section.range.as_ref().map(Expr::value) {
+                if real_range > request_range {
+                    diag.push(RegmapError::Range {
+                        request_range,
+                        real_range,
+                        ctx: inst.ctx.with_hint(format!(
+                            "registers require a range of at least 0x{real_range:x}"
+                        )),
+                    });
+                    real_range
+                } else {
+                    request_range
+                }
+            } else {
+                real_range
+            };
+
+            // NB: Excluded section only reserves its range -> Read as zero
+            let reserved = if section.excluded {
+                register.clear();
+                Some(Reserved::ReadAsZero)
+            } else {
+                section.reserved
+            };
+
+            Self {
+                name: name.clone(),
+                description: section.description.clone(),
+                offset: sec_offset,
+                range,
+                bytes_align: inst.bytes_align,
+                register,
+                reserved,
+                span: span.clone(),
+            }
+        };
+
+        let mut expanded_section = Vec::with_capacity(instances.len());
+        match elab.fit_strategy() {
+            // Sequential allocation: each section is placed after the previous one
+            None => {
+                let mut auto_offset = regmap_offset;
+                for inst in instances.iter() {
+                    let sec_offset = match inst.request {
+                        Some(request) if request < auto_offset => {
+                            diag.push(RegmapError::Offset {
+                                min_offset: auto_offset,
+                                request_offset: request,
+                                ctx: inst.ctx.with_hint(format!(
+                                    "next free offset is 0x{:x} (i.e. 0x{auto_offset:x} absolute)",
+                                    auto_offset - regmap_offset
+                                )),
+                            });
+                            // Use next free offset to keep checking
+                            align_on(inst.bytes_align, auto_offset)
+                        }
+                        Some(request) => request,
+                        None => align_on(inst.bytes_align, auto_offset),
+                    };
+                    let sec = elaborate(inst, sec_offset, diag);
+                    // update auto_offset
+                    auto_offset = sec.offset + sec.range;
+                    expanded_section.push(sec);
+                }
+            }
+            // Fit allocation: locked and explicit offsets are fixed, others fill the free holes
+            Some(strategy) => {
+                let mut allocator = Allocator::new();
+                let mut pending = Vec::new();
+                for inst in instances.iter() {
+                    let name = &inst.elem.0;
+                    let locked = elab
+                        .lock
+                        .as_ref()
+                        .and_then(|lock| lock.section_offset(name));
+                    let fixed = match (locked, inst.request) {
+                        (Some(locked), Some(request)) if locked != request => {
+                            diag.push(RegmapError::LockedOffset {
+                                kind: "section".to_string(),
+                                name: name.clone(),
+                                locked,
+                                request,
+                                ctx: inst.ctx.with_hint(format!(
+                                    "address is frozen in the lock file, restore offset 0x{:x} or remove the lock entry",
+                                    locked.saturating_sub(regmap_offset)
+                                )),
+                            });
+                            Some(locked)
+                        }
+                        (locked, request) => locked.or(request),
+                    };
+                    match fixed {
+                        Some(sec_offset) => {
+                            let sec = elaborate(inst, sec_offset, diag);
+                            allocator.claim(
+                                "section",
+                                name,
+                                sec.offset..sec.offset + sec.range,
+                                &inst.ctx,
+                                diag,
+                            );
+                            expanded_section.push(sec);
+                        }
+                        None => pending.push(inst),
+                    }
+                }
+                for inst in pending {
+                    // Range is only known once registers are elaborated
+                    // -> Elaborate a first time to get the size of the hole
+                    let probe = elaborate(
+                        inst,
+                        align_on(inst.bytes_align, regmap_offset),
+                        &mut Diagnostics::new(),
+                    );
+                    // NB: Power-of-two ranges are naturally aligned to ease address decoding
+                    let bytes_align =
+                        if inst.elem.1.range.is_some() && probe.range.is_power_of_two() {
+                            inst.bytes_align.max(probe.range)
+                        } else {
+                            inst.bytes_align
+                        };
+                    let sec_offset =
+                        allocator.place(strategy, probe.range, bytes_align, regmap_offset);
+                    let sec = elaborate(inst, sec_offset, diag);
+                    allocator.claim(
+                        "section",
+                        &sec.name,
+                        sec.offset..sec.offset + sec.range,
+                        &inst.ctx,
+                        diag,
+                    );
+                    expanded_section.push(sec);
+                }
+            }
+        }
+        // Sort by offset
+        expanded_section.sort_by_key(|a| a.offset);
+
+        expanded_section
+    }
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "  name: {}", self.name)?;
+        writeln!(f, "  description: {}", self.description)?;
+        writeln!(f, "  offset: 0x{:x}", self.offset)?;
+        writeln!(f, "  range:  0x{:x}", self.range)?;
+        writeln!(f, "  bytes_align: {:?}", self.bytes_align)?;
+        write!(f, "  Register: [")?;
+        for reg in self.register.iter() {
+            write!(f, "\n[{reg}]")?;
+        }
+        writeln!(f, "  ]")?;
+        Ok(())
+    }
+}
+
+/// Address window of a regmap definition fused with others
+/// Used to route the requests toward the module of each definition
+#[derive(Debug, Clone, Getters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Slice {
+    module_name: String,
+    description: String,
+    offset: usize,
+    range: usize,
+    /// Names of the sections and blocks defined in this slice
+    section: Vec<String>,
+    block: Vec<String>,
+}
+
+#[derive(Debug, Getters, MutGetters, Serialize, Deserialize)]
+#[getset(get = "pub")]
+pub struct Regmap {
+    #[getset(get_mut = "pub")]
+    module_name: String,
+    description: String,
+    word_size_b: usize,
+    offset: usize,
+    range: usize,
+    ext_pkg: HashSet<String>,
+    section: Vec<Section>,
+    /// Child regmaps instantiated in this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    block: Vec<Block>,
+    /// Values of the parameters used as default value (only the valued ones)
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    param: IndexMap<String, usize>,
+    /// Fused definitions (empty for a single definition)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    slice: Vec<Slice>,
+    /// Toml files of the definition (i.e. regmap files and their includes), child blocks hold
+    /// their own. Empty when loaded from json
+    #[serde(skip)]
+    source: Vec<PathBuf>,
+}
+
+impl Regmap {
+    /// Elaborate a regmap from a list of RegmapOpt
+    /// Stop on the first reported error. Use `check` to retrieved all of them
+    pub fn from_opt(regmaps: &mut [parser::RegmapOpt]) -> Result<Self, RegmapError> {
+        Self::from_opt_with(regmaps, &ElabOpt::default())
+    }
+
+    /// Elaborate a regmap from a list of RegmapOpt with custom elaboration options
+    pub fn from_opt_with(
+        regmaps: &mut [parser::RegmapOpt],
+        elab: &ElabOpt,
+    ) -> Result<Self, RegmapError> {
+        Self::check_with(regmaps, elab).map_err(|diag| {
+            diag.first()
+                .cloned()
+                .expect("Diagnostics are never empty on error")
+        })
+    }
+
+    /// Read and elaborate a regmap from a list of toml files
+    /// All files are parsed and checked, even if some are invalid, to report all the errors in one pass
+    pub fn check_files(files: &[&str]) -> Result<Self, Diagnostics> {
+        Self::check_files_with(files, &ElabOpt::default())
+    }
+
+    /// Read and elaborate a regmap from a list of toml files with custom elaboration options
+    pub fn check_files_with(files: &[&str], elab: &ElabOpt) -> Result<Self, Diagnostics> {
+        let mut diag = Diagnostics::new();
+        let mut regmaps = Vec::with_capacity(files.len());
+        for file in files {
+            match parser::RegmapOpt::read_from(file) {
+                Ok(regmap) => regmaps.push(regmap),
+                Err(err) => diag.push(err),
+            }
+        }
+        if regmaps.is_empty() && !diag.is_empty() {
+            return Err(diag);
+        }
+        match Self::check_with(&mut regmaps, elab) {
+            Ok(regmap) => diag.into_result(regmap),
+            Err(elab_diag) => {
+                diag.extend(elab_diag);
+                Err(diag)
+            }
+        }
+    }
+
+    /// Elaborate a regmap from a list of RegmapOpt
+    /// Elaboration continue after an error to gather all of them in one pass
+    pub fn check(regmaps: &mut [parser::RegmapOpt]) -> Result<Self, Diagnostics> {
+        Self::check_with(regmaps, &ElabOpt::default())
+    }
+
+    /// Elaborate a regmap from a list of RegmapOpt with custom elaboration options
+    pub fn check_with(
+        regmaps: &mut [parser::RegmapOpt],
+        elab: &ElabOpt,
+    ) -> Result<Self, Diagnostics> {
+        Self::elaborate(regmaps, elab, &[])
+    }
+
+    /// Toml files loaded to elaborate the regmap and all its blocks, without duplicate
+    /// NB: Used to track changes of the definition (e.g. rebuild of `include_regmap!` callers)
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        let mut files = self.source.clone();
+        for blk in self.block.iter() {
+            for file in blk.regmap().source_files() {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        files
+    }
+
+    /// Sections of the regmap and of all its blocks with their hierarchical name
+    /// (i.e. `block::section`), in address order of each level
+    pub fn all_sections(&self) -> Vec<(String, &Section)> {
+        let mut sections = self
+            .section
+            .iter()
+            .map(|sec| (sec.name.clone(), sec))
+            .collect::<Vec<_>>();
+        for blk in self.block.iter() {
+            sections.extend(
+                blk.regmap()
+                    .all_sections()
+                    .into_iter()
+                    .map(|(name, sec)| (format!("{}::{name}", blk.name()), sec)),
+            );
+        }
+        sections
+    }
+
+    /// Elaborate a regmap and recursively its blocks
+    /// `chain` is the list of files being elaborated above this one, used to detect cycles
+    fn elaborate(
+        regmaps: &mut [parser::RegmapOpt],
+        elab: &ElabOpt,
+        chain: &[PathBuf],
+    ) -> Result<Self, Diagnostics> {
+        let mut diag = Diagnostics::new();
+
+        //1. Check compliance
+        if regmaps.is_empty() {
+            return Err(RegmapError::NoEntry.into());
+        }
+        // -> Merge the included files and expand the templates, filter the elements with the
+        //    enabled features, then evaluate the constant expressions, properties are literals
+        //    afterward
+        if chain.is_empty() {
+            feature::check_variant(regmaps, elab, &mut diag);
+        }
+        for regmap in regmaps.iter_mut() {
+            include::resolve(regmap, &mut diag);
+            feature::resolve(regmap, elab, &mut diag);
+            expr::resolve(regmap, &mut diag);
+        }
+        // NB: Unresolved expressions would lead to misleading errors -> Stop here
+        if !diag.is_empty() {
+            return Err(diag);
+        }
+        // -> Couldn't merge regmap with != word_size_b
+        let word_size_min = regmaps
+            .iter()
+            .map(|register| register.word_size_b)
+            .min()
+            .unwrap();
+        let word_size_max = regmaps
+            .iter()
+            .map(|register| register.word_size_b)
+            .max()
+            .unwrap();
+        let word_size_b = if word_size_min == word_size_max {
+            word_size_min
+        } else {
+            return Err(RegmapError::WordSize.into());
+        };
+
+        //2. Order regmap slice based on their offset
+        regmaps.sort_by_key(|a| a.offset.as_ref().map(Expr::value));
+        let global_offset = regmaps[0].offset.as_ref().map_or(0, Expr::value);
+
+        //3. Fuse top-level properties
+        let (module_name, description) = {
+            let (name, descr) = regmaps
+                .iter()
+                .map(|r| (r.module_name.as_str(), r.description.as_str()))
+                .collect::<(Vec<_>, Vec<_>)>();
+            (
+                name.as_slice().join("_").to_string(),
+                descr.as_slice().join("\n\n").to_string(),
+            )
+        };
+        let ext_pkg = regmaps
+            .iter()
+            .flat_map(|r| &r.ext_pkg)
+            .cloned()
+            .collect::<HashSet<_>>();
+
+        //4. Expand regmap sections and blocks
+        let mut global_section = Vec::new();
+        let mut global_block = Vec::new();
+        let mut global_slice = Vec::new();
+        let mut auto_offset = 0;
+        let word_bytes = usize::div_ceil(word_size_b, u8::BITS as usize);
+
+        for regmap in regmaps.iter() {
+            // Compute offset and check correctness
+            let regmap_offset = match regmap.offset.as_ref() {
+                Some(ofst) => ofst.value(),
+                None => auto_offset,
+            };
+            if regmap_offset < auto_offset {
+                diag.push(RegmapError::Offset {
+                    min_offset: auto_offset,
+                    request_offset: regmap_offset,
+                    ctx: regmap
+                        .ctx()
+                        .with_hint(format!("next free offset is 0x{auto_offset:x}")),
+                });
+            }
+            let requested_range = regmap.range.value();
+            // Check offset and range properties
+            // Those properties is used to implement clever addr filtering in regmap module
+            let regmap_range = if requested_range.is_power_of_two() {
+                requested_range
+            } else {
+                diag.push(RegmapError::RangePow2 {
+                    range: requested_range,
+                    ctx: regmap.ctx().with_hint(format!(
+                        "next power of two is 0x{:x}",
+                        requested_range.next_power_of_two()
+                    )),
+                });
+                // Use next power of two to keep checking
+                requested_range.next_power_of_two()
+            };
+            let range_mask = regmap_range - 1;
+            if regmap_offset != (regmap_offset & !range_mask) {
+                diag.push(RegmapError::OffsetRange {
+                    offset: regmap_offset,
+                    range: requested_range,
+                    ctx: regmap.ctx().with_hint(format!(
+                        "offset must be a multiple of the range (i.e. 0x{:x})",
+                        regmap_range
+                    )),
+                });
+            }
+
+            // Construct section
+            let section = Section::from_opt(
+                &mut regmap.section.iter(),
+                regmap_offset,
+                word_bytes,
+                regmap.src.as_ref(),
+                elab,
+                &mut diag,
+            );
+
+            // Construct blocks in the remaining space
+            let mut block_chain = chain.to_vec();
+            if let Some(src) = regmap.src.as_ref() {
+                block_chain.push(block::canonical(Path::new(&src.name)));
+            }
+            let block = Block::from_opt(
+                regmap,
+                regmap_offset,
+                &section,
+                elab,
+                &block_chain,
+                &mut diag,
+            );
+
+            // Check range validity for the given regmap
+            let real_range = section
+                .iter()
+                .map(|s| s.offset + s.range)
+                .chain(block.iter().map(|b| b.offset() + b.range()))
+                .max()
+                .unwrap_or(regmap_offset)
+                - regmap_offset;
+            if real_range > regmap_range {
+                diag.push(RegmapError::Range {
+                    request_range: requested_range,
+                    real_range,
+                    ctx: regmap.ctx().with_hint(format!(
+                        "sections and blocks require a range of at least 0x{:x}",
+                        real_range.next_power_of_two()
+                    )),
+                });
+            }
+            // Append section and block to global
+            if regmaps.len() > 1 {
+                global_slice.push(Slice {
+                    module_name: regmap.module_name.clone(),
+                    description: regmap.description.clone(),
+                    offset: regmap_offset,
+                    range: regmap_range,
+                    section: section.iter().map(|s| s.name.clone()).collect(),
+                    block: block.iter().map(|b| b.name().clone()).collect(),
+                });
+            }
+            global_section.extend(section);
+            global_block.extend(block);
+
+            // Update auto_offset for next iteration
+            auto_offset = regmap_offset + regmap_range;
+        }
+
+        // Section names must be unique across all the merged regmaps
+        check_unique(
+            "section",
+            global_section.iter().map(|s| (&s.name, &s.span)),
+            &mut diag,
+        );
+        check_unique(
+            "block",
+            global_block.iter().map(|b| (b.name(), b.span())),
+            &mut diag,
+        );
+
+        // Compute range of the regmap aggregation
+        // It's computed as max_addr - global_offset
+        let global_range = auto_offset - global_offset;
+
+        diag.into_result(Self {
+            module_name,
+            description,
+            word_size_b,
+            ext_pkg,
+            offset: global_offset,
+            range: global_range,
+            param: elab.params.subset(
+                global_section
+                    .iter()
+                    .flat_map(|sec| sec.register.iter())
+                    .flat_map(|reg| reg.params_list()),
+            ),
+            section: global_section,
+            block: global_block,
+            slice: global_slice,
+            source: regmaps
+                .iter()
+                .flat_map(|r| {
+                    r.src
+                        .iter()
+                        .map(|src| PathBuf::from(&src.name))
+                        .chain(r.included.iter().cloned())
+                })
+                .collect(),
+        })
+    }
+}
+
+impl std::fmt::Display for Regmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "description: {}", self.description)?;
+        writeln!(f, "  offset: 0x{:x}", self.offset)?;
+        writeln!(f, "  range:  0x{:x}", self.range)?;
+        writeln!(f, "  word_size_b: {:?}", self.word_size_b)?;
+        writeln!(f, "External package: {:?}", self.ext_pkg)?;
+        write!(f, "Section: [")?;
+        for sec in self.section.iter() {
+            write!(f, "\n[{sec}]")?;
+        }
+        writeln!(f, "]")?;
+        if !self.block.is_empty() {
+            write!(f, "Block: [")?;
+            for blk in self.block.iter() {
+                write!(f, "\n[{blk}]")?;
+            }
+            writeln!(f, "]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = r#"
+module_name="test"
+description="Unit test regmap"
+word_size_b=32
+offset=0x0
+range=0x100
+ext_pkg=[]
+"#;
+
+    /// Elaborate toml snippets (one per file) and list the kind of the reported errors
+    fn error_kinds(bodies: &[&str]) -> Vec<&'static str> {
+        error_kinds_with(bodies, &ElabOpt::default())
+    }
+
+    fn error_kinds_with(bodies: &[&str], elab: &ElabOpt) -> Vec<&'static str> {
+        let mut regmaps = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                parser::RegmapOpt::from_toml_str(
+                    &format!("test_{i}.toml"),
+                    &format!("{HEADER}{body}"),
+                )
+                .expect("Valid toml snippet")
+            })
+            .collect::<Vec<_>>();
+        match Regmap::check_with(&mut regmaps, elab) {
+            Ok(_) => Vec::new(),
+            Err(diag) => diag.iter().map(|err| err.kind()).collect(),
+        }
+    }
+
+    const REG: &str = r#"owner="User"
+read_access="Read"
+write_access="Write"
+"#;
+
+    #[test]
+    fn valid_fields() {
+        let body = format!(
+            "[section.A]\ndescription=\"a\"\n[section.A.register.R]\ndescription=\"r\"\n{REG}\
+             field.a={{size_b=8, offset_b=0, description=\"a\"}}\n\
+             field.b={{size_b=8, description=\"b\"}}\n"
+        );
+        assert!(error_kinds(&[&body]).is_empty());
+    }
+
+    #[test]
+    fn field_overlap() {
+        let body = format!(
+            "[section.A]\ndescription=\"a\"\n[section.A.register.R]\ndescription=\"r\"\n{REG}\
+             field.a={{size_b=8, offset_b=0, description=\"a\"}}\n\
+             field.b={{size_b=8, offset_b=4, description=\"b\"}}\n"
+        );
+        assert_eq!(error_kinds(&[&body]), ["FieldOverlap"]);
+    }
+
+    #[test]
+    fn duplicate_register_after_expansion() {
+        // NB: `R` with suffix `_0` collides with the explicit `R_0`
+        let body = format!(
+            "[section.A]\ndescription=\"a\"\n\
+             [section.A.register.R]\ndescription=\"r\"\nduplicate=[\"_0\", \"_1\"]\n{REG}\
+             [section.A.register.R_0]\ndescription=\"r0\"\n{REG}"
+        );
+        assert!(error_kinds(&[&body]).contains(&"DuplicateName"));
+    }
+
+    #[test]
+    fn duplicate_section_across_files() {
+        let body = format!(
+            "[section.A]\ndescription=\"a\"\n[section.A.register.R]\ndescription=\"r\"\n{REG}"
+        );
+        assert!(error_kinds(&[&body, &body]).contains(&"DuplicateName"));
+    }
+
+    #[test]
+    fn register_overlap() {
+        let body = format!(
+            "[section.A]\ndescription=\"a\"\n\
+             [section.A.register.R]\ndescription=\"r\"\noffset=0x0\n{REG}\
+             [section.A.register.S]\ndescription=\"s\"\noffset=0x0\n{REG}"
+        );
+        // NB: Sequential allocation requires increasing offsets, fit strategies claim ranges
+        assert_eq!(error_kinds(&[&body]), ["Offset"]);
+        let elab = ElabOpt {
+            strategy: Strategy::FirstFit,
+            ..Default::default()
+        };
+        assert_eq!(error_kinds_with(&[&body], &elab), ["AddressOverlap"]);
+    }
+}
@@ -0,0 +1,120 @@
+//!
+//! Parameter values
+//! RTL parameters used as default value are opaque for the tool. A parameter set gives them a
+//! concrete value, thus docs and Sw views could expose the real reset values and the RTL
+//! parameters are emitted with this value as default.
+//!
+//! Values are read from a toml or json file with flat `NAME = value` entries, or from `NAME=VAL`
+//! assignments (i.e. command line).
+//!
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use super::span::SrcFile;
+use super::{DefaultVal, RegmapError};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ParamSet(IndexMap<String, usize>);
+
+impl ParamSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value of a parameter
+    /// NB: Override the previous value if any
+    pub fn insert(&mut self, name: &str, value: usize) {
+        self.0.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.0.get(name).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &usize)> {
+        self.0.iter()
+    }
+
+    /// Read parameter values from a file
+    /// Json format is selected with the `.json` extension, toml is used otherwise
+    pub fn read_from(file: &str) -> Result<Self, RegmapError> {
+        let content = std::fs::read_to_string(file).map_err(|err| RegmapError::Io {
+            file: file.to_string(),
+            msg: err.to_string(),
+        })?;
+        if file.ends_with(".json") {
+            Self::from_json_str(file, &content)
+        } else {
+            Self::from_toml_str(file, &content)
+        }
+    }
+
+    /// Parse parameter values from a toml string
+    /// NB: file is only used to give context in error message
+    pub fn from_toml_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        toml::from_str(content).map_err(|err| {
+            let (line, column) = match err.span() {
+                Some(span) => SrcFile::new(file, content).line_col(span.start),
+                None => (0, 0),
+            };
+            RegmapError::TomlSyntax {
+                file: file.to_string(),
+                line,
+                column,
+                msg: err.message().trim_end().to_string(),
+            }
+        })
+    }
+
+    /// Parse parameter values from a json string
+    /// NB: file is only used to give context in error message
+    pub fn from_json_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        serde_json::from_str(content).map_err(|err| RegmapError::JsonSyntax {
+            file: file.to_string(),
+            line: err.line(),
+            column: err.column(),
+            msg: err.to_string(),
+        })
+    }
+
+    /// Parse a `NAME=VAL` assignment
+    /// Value is decimal or hexadecimal with the `0x` prefix
+    pub fn parse_assign(assign: &str) -> Result<(String, usize), String> {
+        let (name, value) = assign
+            .split_once('=')
+            .ok_or_else(|| format!("expect NAME=VAL, get `{assign}`"))?;
+        let (name, value) = (name.trim(), value.trim());
+        if name.is_empty() {
+            return Err(format!("missing parameter name in `{assign}`"));
+        }
+        let value = match value.strip_prefix("0x") {
+            Some(hex) => usize::from_str_radix(&hex.replace('_', ""), 16),
+            None => value.replace('_', "").parse::<usize>(),
+        }
+        .map_err(|err| format!("invalid value for parameter `{name}`: {err}"))?;
+        Ok((name.to_string(), value))
+    }
+
+    /// Concrete value of a default, None if it relies on a parameter without value
+    /// NB: ParamsField is resolved from the fields (cf. Register::from_opt)
+    pub fn resolve(&self, dflt: &DefaultVal) -> Option<usize> {
+        match dflt {
+            DefaultVal::Cst(val) => Some(*val),
+            DefaultVal::Param(param) => self.get(param),
+            DefaultVal::ParamsField { .. } => None,
+        }
+    }
+
+    /// Values of the given parameters, parameters without value are skipped
+    pub fn subset(&self, names: impl Iterator<Item = String>) -> IndexMap<String, usize> {
+        names
+            .filter_map(|name| self.get(&name).map(|val| (name, val)))
+            .collect()
+    }
+}
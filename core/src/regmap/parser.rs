@@ -0,0 +1,349 @@
+//!
+//! Describe Register map abstract view
+//! This is used for easy definition from the user, a lot of field are optionnals
+//!
+//! Also provide a set of function to serde it from/toward toml file
+//!
+pub use super::expr::Expr;
+use super::span::{SrcCtx, SrcFile, SrcSpan};
+use super::{DefaultVal, RegmapError};
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use toml::Spanned;
+
+// NB: Access enums are shared with the runtime model
+pub use crate::model::{Owner, ReadAccess, Reserved, WriteAccess};
+
+/// Default value as written by the user
+/// Same as DefaultVal except that constant could be an expression
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+pub enum DefaultOpt {
+    Cst(Expr),
+    Param(String),
+    ParamsField {
+        params: Vec<String>,
+        name_val: Vec<(String, String)>,
+    },
+}
+
+impl DefaultOpt {
+    /// Default value with resolved expression
+    pub fn value(&self) -> DefaultVal {
+        match self {
+            Self::Cst(expr) => DefaultVal::Cst(expr.value()),
+            Self::Param(param) => DefaultVal::Param(param.clone()),
+            Self::ParamsField { params, name_val } => DefaultVal::ParamsField {
+                params: params.clone(),
+                name_val: name_val.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FieldOpt {
+    pub description: String,
+    /// Width in bits
+    pub size_b: Expr,
+    /// RTL parameter giving the actual width, size_b is then the maximum width
+    pub size_param: Option<String>,
+    /// Position of the lsb, packed after the previous field when omitted
+    pub offset_b: Option<Expr>,
+    pub default: Option<DefaultOpt>,
+    /// Reserved bits, no port is generated for them
+    pub reserved: Option<Reserved>,
+    /// Feature predicate, the element is excluded when it doesn't hold
+    #[serde(rename = "if")]
+    pub cond: Option<String>,
+    /// Excluded by its predicate but kept to reserve its space
+    #[serde(skip)]
+    pub excluded: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterOpt {
+    pub description: String,
+    /// Owner and access rights are required, except for reserved registers (i.e. implied by the
+    /// policy)
+    pub owner: Option<Owner>,
+    pub read_access: Option<ReadAccess>,
+    pub write_access: Option<WriteAccess>,
+    /// Reserved register, no port is generated for it
+    pub reserved: Option<Reserved>,
+    pub default: Option<DefaultOpt>,
+    pub bytes_align: Option<Expr>,
+    /// Offset in the section, allocated when omitted
+    pub offset: Option<Expr>,
+    #[schemars(with = "Option<IndexMap<String, FieldOpt>>")]
+    pub field: Option<IndexMap<String, Spanned<FieldOpt>>>,
+    /// Suffixes of the register copies
+    pub duplicate: Option<Vec<String>>,
+    /// Feature predicate, the element is excluded when it doesn't hold
+    #[serde(rename = "if")]
+    pub cond: Option<String>,
+    /// Excluded by its predicate but kept to reserve its space
+    #[serde(skip)]
+    pub excluded: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SectionOpt {
+    pub description: String,
+    /// Offset in the regmap, allocated when omitted
+    pub offset: Option<Expr>,
+    pub range: Option<Expr>,
+    pub bytes_align: Option<Expr>,
+    /// Suffixes of the section copies
+    pub duplicate: Option<Vec<String>>,
+    /// Reserved address range, such section only has a range
+    pub reserved: Option<Reserved>,
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, RegisterOpt>")]
+    pub register: IndexMap<String, Spanned<RegisterOpt>>,
+    /// Registers built from a register template
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[schemars(with = "IndexMap<String, InstanceOpt>")]
+    pub instance: IndexMap<String, Spanned<InstanceOpt>>,
+    /// Feature predicate, the element is excluded when it doesn't hold
+    #[serde(rename = "if")]
+    pub cond: Option<String>,
+    /// Excluded by its predicate but kept to reserve its space
+    #[serde(skip)]
+    pub excluded: bool,
+    /// Source file of an included section. Sections of the regmap file itself rely on it
+    #[serde(skip)]
+    pub src: Option<Arc<SrcFile>>,
+}
+
+/// Instance of a template, other keys override the template properties
+/// NB: Overrides are only checked once merged in the template
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct InstanceOpt {
+    pub template: String,
+    #[serde(flatten)]
+    #[schemars(with = "serde_json::Map<String, serde_json::Value>")]
+    pub overrides: toml::Table,
+}
+
+/// Reusable shapes of registers and sections
+/// NB: Kept as raw tables, they are only checked once instantiated with the overrides
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateOpt {
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub register: IndexMap<String, Spanned<toml::Table>>,
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, serde_json::Map<String, serde_json::Value>>")]
+    pub section: IndexMap<String, Spanned<toml::Table>>,
+}
+
+impl TemplateOpt {
+    pub fn is_empty(&self) -> bool {
+        self.register.is_empty() && self.section.is_empty()
+    }
+}
+
+/// Files to include, a single path or a list of paths
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum IncludeOpt {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl IncludeOpt {
+    pub fn files(&self) -> &[String] {
+        match self {
+            Self::One(file) => std::slice::from_ref(file),
+            Self::Many(files) => files,
+        }
+    }
+}
+
+/// Content of an included file
+/// Only reusable parts are allowed, the header stays in the including regmap
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FragmentOpt {
+    #[schemars(with = "Option<IncludeOpt>")]
+    pub include: Option<Spanned<IncludeOpt>>,
+    #[serde(default)]
+    pub template: TemplateOpt,
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, SectionOpt>")]
+    pub section: IndexMap<String, Spanned<SectionOpt>>,
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, InstanceOpt>")]
+    pub instance: IndexMap<String, Spanned<InstanceOpt>>,
+}
+
+/// Instance of another regmap definition as a child block
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct BlockOpt {
+    /// Definition of the child regmap, relative to the including file
+    pub file: String,
+    pub description: Option<String>,
+    pub offset: Option<Expr>,
+    pub duplicate: Option<Vec<String>>,
+}
+
+/// Register map definition, i.e. content of a toml file
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RegmapOpt {
+    pub module_name: String,
+    pub description: String,
+    /// Width of the registers in bits
+    pub word_size_b: usize,
+    /// Base address of the regmap
+    pub offset: Option<Expr>,
+    /// Address range of the regmap in bytes
+    pub range: Expr,
+    /// SystemVerilog packages imported by the generated RTL
+    pub ext_pkg: Vec<String>,
+    /// Files merged in this definition, relative to the including file
+    #[schemars(with = "Option<IncludeOpt>")]
+    pub include: Option<Spanned<IncludeOpt>>,
+    /// Named constants usable in expressions, evaluated in definition order
+    #[serde(default, rename = "const")]
+    #[schemars(with = "IndexMap<String, Expr>")]
+    pub constant: IndexMap<String, Spanned<Expr>>,
+    /// Named sets of features (i.e. build variants)
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub variant: IndexMap<String, Vec<String>>,
+    #[serde(default, skip_serializing_if = "TemplateOpt::is_empty")]
+    pub template: TemplateOpt,
+    #[serde(default)]
+    #[schemars(with = "IndexMap<String, SectionOpt>")]
+    pub section: IndexMap<String, Spanned<SectionOpt>>,
+    /// Sections built from a section template
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    #[schemars(with = "IndexMap<String, InstanceOpt>")]
+    pub instance: IndexMap<String, Spanned<InstanceOpt>>,
+    /// Child regmaps instantiated in this one
+    #[schemars(with = "Option<IndexMap<String, BlockOpt>>")]
+    pub block: Option<IndexMap<String, Spanned<BlockOpt>>>,
+    /// Source file of the definition. Used to locate elements in error messages
+    #[serde(skip)]
+    pub src: Option<Arc<SrcFile>>,
+    /// Files loaded while resolving `include` (nested ones included)
+    #[serde(skip)]
+    pub included: Vec<PathBuf>,
+}
+
+impl RegmapOpt {
+    /// Read and parse a toml register map definition
+    pub fn read_from(file: &str) -> Result<Self, RegmapError> {
+        let file_str = fs::read_to_string(file).map_err(|err| RegmapError::Io {
+            file: file.to_string(),
+            msg: err.to_string(),
+        })?;
+        Self::from_toml_str(file, &file_str)
+    }
+
+    /// Parse a toml register map definition from a string
+    /// NB: file is only used to give context in error message
+    pub fn from_toml_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        let src = SrcFile::new(file, content);
+        let mut regmap: Self = toml::from_str(content).map_err(|err| {
+            // NB: Unknown keys are located and come with a hint, other errors are reported as is
+            if let (Some((key, hint)), Some(span)) = (unknown_key(err.message()), err.span()) {
+                return RegmapError::UnknownKey {
+                    key,
+                    ctx: SrcCtx::new(format!("file `{file}`"), Some(SrcSpan::new(&src, span)))
+                        .with_hint(hint),
+                };
+            }
+            let (line, column) = match err.span() {
+                Some(span) => src.line_col(span.start),
+                None => (0, 0),
+            };
+            RegmapError::TomlSyntax {
+                file: file.to_string(),
+                line,
+                column,
+                msg: err.message().trim_end().to_string(),
+            }
+        })?;
+        regmap.src = Some(src);
+        Ok(regmap)
+    }
+
+    /// Locate a spanned element of this regmap
+    pub fn span_of<T>(&self, elem: &Spanned<T>) -> Option<SrcSpan> {
+        self.src.as_ref().map(|src| SrcSpan::new(src, elem.span()))
+    }
+
+    /// Error context of the regmap header
+    pub fn ctx(&self) -> SrcCtx {
+        let what = match self.src.as_ref() {
+            Some(src) => format!("regmap `{}` ({})", self.module_name, src.name),
+            None => format!("regmap `{}`", self.module_name),
+        };
+        SrcCtx::new(what, None)
+    }
+
+    /// JSON Schema of the regmap definition, used by editors to validate and complete toml files
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&schemars::schema_for!(RegmapOpt))
+            .expect("Schema serialization can not fail")
+    }
+}
+
+impl FragmentOpt {
+    /// JSON Schema of the included files
+    pub fn json_schema() -> String {
+        serde_json::to_string_pretty(&schemars::schema_for!(FragmentOpt))
+            .expect("Schema serialization can not fail")
+    }
+}
+
+/// Extract the key of an unknown key error and build a hint to fix it
+/// NB: Serde lists the expected keys in the message, the closest one is suggested
+pub(super) fn unknown_key(msg: &str) -> Option<(String, String)> {
+    let (key, expected) = msg.strip_prefix("unknown field `")?.split_once('`')?;
+    // Expected keys are quoted with backticks -> odd items once split
+    let expected = expected.split('`').skip(1).step_by(2).collect::<Vec<_>>();
+    let hint = match did_you_mean(key, &expected) {
+        Some(close) => format!("did you mean `{close}`?"),
+        None if expected.is_empty() => "no property is expected here".to_string(),
+        None => format!("valid properties are [{}]", expected.join(", ")),
+    };
+    Some((key.to_string(), hint))
+}
+
+/// Closest candidate of a misspelled name, if any is close enough
+fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    // NB: Allow one edit every three characters
+    let max = name.chars().count().max(3) / 3;
+    candidates
+        .iter()
+        .map(|cand| (edit_distance(name, cand), *cand))
+        .filter(|(dist, _)| *dist <= max)
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, cand)| cand)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let subst = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = subst.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
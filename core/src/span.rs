@@ -0,0 +1,163 @@
+//!
+//! Track source location of the register map elements
+//! Used to report errors compiler-style with file:line:col, offending snippet and hint
+//!
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use core::fmt;
+use core::ops::Range;
+
+/// Source file content
+/// Shared by all the elements parsed from it
+#[derive(Debug, PartialEq, Eq)]
+pub struct SrcFile {
+    pub name: String,
+    pub content: String,
+}
+
+impl SrcFile {
+    pub fn new(name: &str, content: &str) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.to_string(),
+            content: content.to_string(),
+        })
+    }
+
+    /// Convert a byte offset in (line, column) coordinates
+    /// NB: Both coordinates start at 1
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let before = &self.content[..offset.min(self.content.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+        (line, column)
+    }
+}
+
+/// Location of an element in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrcSpan {
+    file: Arc<SrcFile>,
+    range: Range<usize>,
+}
+
+impl SrcSpan {
+    pub fn new(file: &Arc<SrcFile>, range: Range<usize>) -> Self {
+        Self {
+            file: file.clone(),
+            range,
+        }
+    }
+
+    pub fn file(&self) -> &str {
+        &self.file.name
+    }
+
+    pub fn range(&self) -> &Range<usize> {
+        &self.range
+    }
+
+    /// Start position of the span in (line, column) coordinates
+    pub fn line_col(&self) -> (usize, usize) {
+        self.file.line_col(self.range.start)
+    }
+
+    /// First line of the span
+    /// Header tables span over their whole body, only the header line is of interest
+    pub fn snippet(&self) -> &str {
+        let start = self.range.start.min(self.file.content.len());
+        let line_start = self.file.content[..start]
+            .rfind('\n')
+            .map(|p| p + 1)
+            .unwrap_or(0);
+        let line_end = self.file.content[start..]
+            .find('\n')
+            .map(|p| start + p)
+            .unwrap_or(self.file.content.len());
+        self.file.content[line_start..line_end].trim_end()
+    }
+}
+
+impl fmt::Display for SrcSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, col) = self.line_col();
+        write!(f, "{}:{line}:{col}", self.file.name)
+    }
+}
+
+/// Context attached to an error
+/// Describe the offending element, its location and an optional hint to fix it
+/// NB: Content is boxed to keep errors small
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SrcCtx(Box<SrcCtxInner>);
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct SrcCtxInner {
+    what: String,
+    span: Option<SrcSpan>,
+    hint: Option<String>,
+}
+
+impl SrcCtx {
+    pub fn new(what: String, span: Option<SrcSpan>) -> Self {
+        Self(Box::new(SrcCtxInner {
+            what,
+            span,
+            hint: None,
+        }))
+    }
+
+    /// Copy of the context with an hint attached
+    pub fn with_hint(&self, hint: String) -> Self {
+        Self(Box::new(SrcCtxInner {
+            hint: Some(hint),
+            ..(*self.0).clone()
+        }))
+    }
+
+    pub fn what(&self) -> &str {
+        &self.0.what
+    }
+
+    pub fn span(&self) -> Option<&SrcSpan> {
+        self.0.span.as_ref()
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.0.hint.as_deref()
+    }
+}
+
+impl fmt::Display for SrcCtx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.span() {
+            Some(span) => {
+                let (line, col) = span.line_col();
+                let snippet = span.snippet();
+                let gutter = " ".repeat(line.to_string().len());
+                writeln!(f, "{gutter}--> {span}")?;
+                writeln!(f, "{gutter} |")?;
+                writeln!(f, "{line} | {snippet}")?;
+                let underline = snippet.len().saturating_sub(col - 1).max(1);
+                writeln!(
+                    f,
+                    "{gutter} | {}{}",
+                    " ".repeat(col - 1),
+                    "^".repeat(underline)
+                )?;
+                write!(f, "{gutter} = in {}", self.what())?;
+                if let Some(hint) = self.hint() {
+                    write!(f, "\n{gutter} = hint: {hint}")?;
+                }
+            }
+            None => {
+                write!(f, "  = in {}", self.what())?;
+                if let Some(hint) = self.hint() {
+                    write!(f, "\n  = hint: {hint}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+//!
+//! Compile-time register map embedding
+//! `include_regmap!` parses and checks the toml definition while the driver compiles, errors are
+//! reported as compiler errors. It expands to typed register wrappers with const offsets, thus
+//! the toml stays the single source of truth for both RTL and Rust.
+//!
+//! Paths are relative to the manifest directory of the calling crate (i.e. `CARGO_MANIFEST_DIR`)
+//! and multiple files are fused in one register map.
+//! NB: Expanded code only relies on `core`, thus it could be used in `no_std` firmware.
+//! Re-exported by `hw_regmap` with the `macros` feature.
+//!
+
+use std::path::Path;
+
+use hw_regmap_core::Regmap;
+use hw_regmap_core::model::field_mask;
+use hw_regmap_core::regmap::block::Block;
+use hw_regmap_core::regmap::parser::Reserved;
+use hw_regmap_core::regmap::{Register, Section};
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Literal, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::{LitStr, Token, parse_macro_input};
+
+/// Embed a register map definition checked at compile time
+///
+/// ``` ignore
+/// mod regs {
+///     hw_regmap::include_regmap!("config/example.toml");
+/// }
+/// let ntt = regs::info::NttInternal(bus.read(regs::info::NttInternal::OFS));
+/// let psi = ntt.psi();
+/// ```
+///
+/// Each section is a module with its `OFS` and `RANGE`, each register a wrapper of the register
+/// word with its `OFS`, `RESET`, field constants (`<FIELD>_SHIFT`, `<FIELD>_WIDTH`,
+/// `<FIELD>_MASK`) and field accessors (`<field>()` getters and `with_<field>()` setters).
+/// Child blocks are modules holding their sections.
+#[proc_macro]
+pub fn include_regmap(input: TokenStream) -> TokenStream {
+    let files = parse_macro_input!(input with Punctuated::<LitStr, Token![,]>::parse_terminated);
+    let Some(first) = files.first() else {
+        return quote!(::core::compile_error!("expect at least one toml file");).into();
+    };
+
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let paths = files
+        .iter()
+        .map(|file| Path::new(&root).join(file.value()).display().to_string())
+        .collect::<Vec<_>>();
+    expand(&paths, first.span()).into()
+}
+
+/// Check the toml definitions and expand them, errors are expanded as `compile_error!`
+/// `span` locates the errors in the caller
+fn expand(paths: &[String], span: Span) -> TokenStream2 {
+    let regmap = match Regmap::check_files(&paths.iter().map(|p| p.as_str()).collect::<Vec<_>>()) {
+        Ok(regmap) => regmap,
+        Err(diag) => {
+            // NB: All the errors are reported at once, as with the `check` subcommand
+            let errors = diag.iter().map(|err| {
+                let msg = err.to_string();
+                quote_spanned!(span=> ::core::compile_error!(#msg);)
+            });
+            return quote!(#(#errors)*);
+        }
+    };
+
+    let word = match regmap.word_size_b() {
+        8 => format_ident!("u8"),
+        16 => format_ident!("u16"),
+        32 => format_ident!("u32"),
+        64 => format_ident!("u64"),
+        other => {
+            let msg = format!("Unsupported word size of {other} bits (i.e. 8, 16, 32 or 64)");
+            return quote_spanned!(span=> ::core::compile_error!(#msg););
+        }
+    };
+
+    // Rebuild the caller when the definition changes
+    // NB: Every loaded file is tracked, i.e. included files and child blocks
+    let tracked = regmap.source_files().into_iter().map(|path| {
+        let path = path.display().to_string();
+        quote!(
+            const _: &[u8] = ::core::include_bytes!(#path);
+        )
+    });
+    let body = expand_regmap(&regmap, "", &word);
+    quote!(#(#tracked)* #body)
+}
+
+/// Sections and blocks of a regmap
+/// `prefix` is the hierarchical path of the regmap (i.e. `block::`), used in register names
+fn expand_regmap(regmap: &Regmap, prefix: &str, word: &Ident) -> TokenStream2 {
+    let ofs = Literal::usize_unsuffixed(*regmap.offset());
+    let range = Literal::usize_unsuffixed(*regmap.range());
+    let sections = regmap
+        .section()
+        .iter()
+        .map(|sec| expand_section(sec, prefix, word));
+    let blocks = regmap
+        .block()
+        .iter()
+        .map(|blk| expand_block(blk, prefix, word));
+    quote! {
+        pub const OFS: usize = #ofs;
+        pub const RANGE: usize = #range;
+        #(#sections)*
+        #(#blocks)*
+    }
+}
+
+fn expand_block(blk: &Block, prefix: &str, word: &Ident) -> TokenStream2 {
+    let name = ident(&snake(blk.name()));
+    let doc = blk.description();
+    let body = expand_regmap(blk.regmap(), &format!("{prefix}{}::", blk.name()), word);
+    quote! {
+        #[doc = #doc]
+        pub mod #name {
+            #body
+        }
+    }
+}
+
+fn expand_section(sec: &Section, prefix: &str, word: &Ident) -> TokenStream2 {
+    let name = ident(&snake(sec.name()));
+    let doc = match sec.reserved() {
+        Some(policy) => format!("Reserved ({policy}): {}", sec.description()),
+        None => sec.description().clone(),
+    };
+    let ofs = Literal::usize_unsuffixed(*sec.offset());
+    let range = Literal::usize_unsuffixed(*sec.range());
+    let sec_path = format!("{prefix}{}", sec.name());
+    let registers = sec
+        .register()
+        .iter()
+        .map(|reg| expand_register(reg, &sec_path, word));
+    quote! {
+        #[doc = #doc]
+        pub mod #name {
+            pub const OFS: usize = #ofs;
+            pub const RANGE: usize = #range;
+            #(#registers)*
+        }
+    }
+}
+
+fn expand_register(reg: &Register, sec_path: &str, word: &Ident) -> TokenStream2 {
+    let name = ident(&camel(reg.name()));
+    let doc = match reg.reserved() {
+        Some(policy) => format!("Reserved ({policy}): {}", reg.description()),
+        None => reg.description().clone(),
+    };
+    let flat_name = format!("{sec_path}::{}", reg.name());
+    let ofs = Literal::usize_unsuffixed(*reg.offset());
+    let readable = reg.read_access().is_read();
+    let writable = reg.write_access().is_write();
+
+    // NB: Reset is only available if it doesn't rely on a parameter without value
+    let reset = reg.reset().map(|reset| {
+        let reset = Literal::u64_unsuffixed(reset as u64);
+        quote! {
+            pub const RESET: #word = #reset;
+        }
+    });
+    let default = reg.reset().map(|_| {
+        quote! {
+            impl ::core::default::Default for #name {
+                fn default() -> Self {
+                    Self(Self::RESET)
+                }
+            }
+        }
+    });
+
+    let (mut rsvd_mask, mut preserve_mask) = (0_u64, 0_u64);
+    let mut fields = Vec::new();
+    for field in reg.field().iter().flatten() {
+        let mask = field_mask(field.width()) << field.offset_b();
+        match field.reserved() {
+            Some(Reserved::PreserveOnWrite) => {
+                rsvd_mask |= mask;
+                preserve_mask |= mask;
+                continue;
+            }
+            Some(_) => {
+                rsvd_mask |= mask;
+                continue;
+            }
+            None => {}
+        }
+        let doc = field.description();
+        let upper = snake(field.name()).to_ascii_uppercase();
+        let (shift_c, width_c, mask_c) = (
+            ident(&format!("{upper}_SHIFT")),
+            ident(&format!("{upper}_WIDTH")),
+            ident(&format!("{upper}_MASK")),
+        );
+        let getter = ident(&snake(field.name()));
+        let setter = ident(&format!("with_{}", snake(field.name())));
+        let shift = Literal::usize_unsuffixed(*field.offset_b());
+        let width = Literal::usize_unsuffixed(field.width());
+        let mask = Literal::u64_unsuffixed(mask);
+        let get = readable.then(|| {
+            quote! {
+                #[doc = #doc]
+                pub const fn #getter(self) -> #word {
+                    (self.0 & Self::#mask_c) >> Self::#shift_c
+                }
+            }
+        });
+        let set = writable.then(|| {
+            quote! {
+                #[doc = #doc]
+                pub const fn #setter(self, val: #word) -> Self {
+                    Self((self.0 & !Self::#mask_c) | ((val << Self::#shift_c) & Self::#mask_c))
+                }
+            }
+        });
+        fields.push(quote! {
+            pub const #shift_c: u32 = #shift;
+            pub const #width_c: u32 = #width;
+            pub const #mask_c: #word = #mask;
+            #get
+            #set
+        });
+    }
+    let masks = (rsvd_mask != 0).then(|| {
+        let (rsvd_mask, preserve_mask) = (
+            Literal::u64_unsuffixed(rsvd_mask),
+            Literal::u64_unsuffixed(preserve_mask),
+        );
+        quote! {
+            /// Reserved bits, must be written back as read in PRESERVE_MASK
+            pub const RSVD_MASK: #word = #rsvd_mask;
+            pub const PRESERVE_MASK: #word = #preserve_mask;
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct #name(pub #word);
+
+        impl #name {
+            /// Name in the flatten view of the regmap (i.e. `section::register`)
+            pub const NAME: &'static str = #flat_name;
+            pub const OFS: usize = #ofs;
+            pub const READABLE: bool = #readable;
+            pub const WRITABLE: bool = #writable;
+            #reset
+            #masks
+            #(#fields)*
+        }
+
+        #default
+    }
+}
+
+/// Rust identifier of a regmap name
+/// Invalid characters are replaced by `_` and keywords are suffixed by `_`
+fn ident(name: &str) -> Ident {
+    let mut name = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    if syn::parse_str::<Ident>(&name).is_err() {
+        name.push('_');
+    }
+    Ident::new(&name, Span::call_site())
+}
+
+/// Lower snake case, i.e. `NttRdxCut` -> `ntt_rdx_cut`
+fn snake(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() && prev_lower {
+            snake.push('_');
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        snake.push(c.to_ascii_lowercase());
+    }
+    snake
+}
+
+/// Upper camel case, i.e. `ntt_rdx_cut` -> `NttRdxCut`
+fn camel(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_as_compile_error() {
+        let root = env!("CARGO_MANIFEST_DIR");
+        let paths = [format!("{root}/tests/fixtures/overlap.toml")];
+        let tokens = expand(&paths, Span::call_site()).to_string();
+        assert!(tokens.starts_with(":: core :: compile_error !"));
+        assert!(tokens.contains("overlaps field"));
+    }
+
+    #[test]
+    fn tracked_files() {
+        let root = format!("{}/../config/debug", env!("CARGO_MANIFEST_DIR"));
+        for (top, files) in [
+            (
+                "hier/top.toml",
+                &["hier/dma.toml", "hier/timer.toml", "hier/prescaler.toml"][..],
+            ),
+            (
+                "include/core.toml",
+                &["include/common/xrt.toml", "include/common/perf.toml"][..],
+            ),
+        ] {
+            let tokens = expand(&[format!("{root}/{top}")], Span::call_site()).to_string();
+            for file in std::iter::once(&top).chain(files) {
+                assert!(
+                    tokens.contains(&format!(":: core :: include_bytes ! (\"{root}/{file}\")")),
+                    "`{file}` isn't tracked"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn missing_file() {
+        let tokens = expand(&["missing.toml".to_string()], Span::call_site()).to_string();
+        assert!(tokens.contains("compile_error"));
+        assert!(tokens.contains("missing.toml"));
+    }
+
+    #[test]
+    fn idents() {
+        assert_eq!(snake("NttRdxCut"), "ntt_rdx_cut");
+        assert_eq!(camel("ntt_rdx_cut"), "NttRdxCut");
+        assert_eq!(ident("type").to_string(), "type_");
+        assert_eq!(ident("0x-cfg").to_string(), "_0x_cfg");
+    }
+}
@@ -0,0 +1,62 @@
+//!
+//! Expand the example definitions and check the generated constants against the runtime view
+//!
+
+use hw_regmap::FlatRegmap;
+
+mod regs {
+    hw_regmap_macros::include_regmap!("../config/example.toml");
+}
+
+mod hier {
+    hw_regmap_macros::include_regmap!("../config/debug/hier/top.toml");
+}
+
+fn flat(file: &str) -> FlatRegmap {
+    FlatRegmap::from_file(&[file]).expect("Valid definition")
+}
+
+#[test]
+fn register_offsets() {
+    use regs::info::{NttInternal, NttRdxCut, Version};
+    let flat = flat("../config/example.toml");
+    for (name, ofs) in [
+        (Version::NAME, Version::OFS),
+        (NttInternal::NAME, NttInternal::OFS),
+        (NttRdxCut::NAME, NttRdxCut::OFS),
+    ] {
+        assert_eq!(*flat.get_register(name).unwrap().offset(), ofs, "{name}");
+    }
+    assert_eq!(regs::RANGE, *flat.range());
+    assert_eq!(regs::info::OFS, Version::OFS);
+}
+
+#[test]
+fn field_accessors() {
+    use regs::info::NttInternal;
+    let flat = flat("../config/example.toml");
+    let reg = flat.get_register(NttInternal::NAME).unwrap();
+    let raw = 0x0403_0201;
+    let fields = reg.as_field(raw);
+    let ntt = NttInternal(raw);
+    assert_eq!(ntt.radix(), fields["radix"]);
+    assert_eq!(ntt.psi(), fields["psi"]);
+    assert_eq!(ntt.div(), fields["div"]);
+    assert_eq!(ntt.delta(), fields["delta"]);
+    assert_eq!(NttInternal::PSI_SHIFT, 8);
+    assert_eq!(NttInternal::PSI_WIDTH, 8);
+    assert_eq!(NttInternal::PSI_MASK, 0xff00);
+    const { assert!(NttInternal::READABLE && !NttInternal::WRITABLE) };
+}
+
+#[test]
+fn block_offsets() {
+    use hier::dma_1::cfg::Ctrl;
+    let flat = flat("../config/debug/hier/top.toml");
+    assert_eq!(Ctrl::NAME, "dma_1::Cfg::Ctrl");
+    assert_eq!(*flat.get_register(Ctrl::NAME).unwrap().offset(), Ctrl::OFS);
+    let ctrl = Ctrl(0).with_len(0x40).with_start(1);
+    assert_eq!(ctrl.0, 0x8000_0040);
+    assert_eq!(ctrl.len(), 0x40);
+    assert_eq!(Ctrl::default().0, Ctrl::RESET);
+}
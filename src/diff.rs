@@ -0,0 +1,390 @@
+//!
+//! Compare two elaborated register maps
+//! Used to track the changes between two revisions of the same definition and flag the ones that
+//! break the software view (i.e. ABI-breaking changes).
+//!
+//! Registers are matched by `section::register` name and fields by name inside a register.
+//!
+
+use serde::Serialize;
+
+use crate::regmap::parser::{Owner, ReadAccess, Reserved, WriteAccess};
+use crate::regmap::{DefaultVal, Field, Register, Regmap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// Element only available in the new regmap
+    Added,
+    /// Element only available in the old regmap
+    Removed,
+    /// Register offset or field bit position changed
+    Moved,
+    /// Field or word width changed
+    Resized,
+    /// Register owner, access rights or reservation changed
+    Access,
+    /// Reset value changed
+    Default,
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Moved => "moved",
+            Self::Resized => "resized",
+            Self::Access => "access",
+            Self::Default => "default",
+        };
+        f.pad(kind)
+    }
+}
+
+/// Single difference between two regmaps
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Changed element with `section::register[::field]` syntax
+    pub element: String,
+    /// Old value (if any)
+    pub old: Option<String>,
+    /// New value (if any)
+    pub new: Option<String>,
+    /// Change breaks software built against the old regmap
+    pub breaking: bool,
+}
+
+impl std::fmt::Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:<8} {}",
+            if self.breaking {
+                "[BREAKING]"
+            } else {
+                "          "
+            },
+            self.kind,
+            self.element
+        )?;
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, ": {old} -> {new}"),
+            (Some(old), None) => write!(f, ": {old}"),
+            (None, Some(new)) => write!(f, ": {new}"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// List of changes between two regmaps
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RegmapDiff {
+    changes: Vec<Change>,
+}
+
+impl RegmapDiff {
+    /// Compare old and new regmaps
+    /// Changes are listed in old regmap order, followed by the added registers
+    pub fn new(old: &Regmap, new: &Regmap) -> Self {
+        let mut diff = Self::default();
+
+        if old.word_size_b() != new.word_size_b() {
+            diff.push(
+                ChangeKind::Resized,
+                "word_size_b".to_string(),
+                Some(old.word_size_b().to_string()),
+                Some(new.word_size_b().to_string()),
+                true,
+            );
+        }
+
+        let old_regs = registers(old);
+        let new_regs = registers(new);
+        for (name, reg) in old_regs.iter() {
+            match new_regs.iter().find(|(n, _)| n == name) {
+                Some((_, new_reg)) => diff.register(name, reg, new_reg),
+                None => diff.push(
+                    ChangeKind::Removed,
+                    name.clone(),
+                    Some(format!("0x{:x}", reg.offset())),
+                    None,
+                    true,
+                ),
+            }
+        }
+        for (name, reg) in new_regs.iter() {
+            if !old_regs.iter().any(|(n, _)| n == name) {
+                diff.push(
+                    ChangeKind::Added,
+                    name.clone(),
+                    None,
+                    Some(format!("0x{:x}", reg.offset())),
+                    false,
+                );
+            }
+        }
+        diff
+    }
+
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// At least one change breaks the software view
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.breaking)
+    }
+
+    /// Only the ABI-breaking changes
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|c| c.breaking)
+    }
+
+    /// Serialize changes list in json
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("RegmapDiff serialization can not fail")
+    }
+
+    fn push(
+        &mut self,
+        kind: ChangeKind,
+        element: String,
+        old: Option<String>,
+        new: Option<String>,
+        breaking: bool,
+    ) {
+        self.changes.push(Change {
+            kind,
+            element,
+            old,
+            new,
+            breaking,
+        });
+    }
+
+    /// Compare two versions of the same register
+    fn register(&mut self, name: &str, old: &Register, new: &Register) {
+        if old.offset() != new.offset() {
+            self.push(
+                ChangeKind::Moved,
+                name.to_string(),
+                Some(format!("0x{:x}", old.offset())),
+                Some(format!("0x{:x}", new.offset())),
+                true,
+            );
+        }
+
+        // NB: Gaining a right is transparent for the software, losing one or changing the owner isn't
+        let old_access = (old.owner(), old.read_access(), old.write_access());
+        let new_access = (new.owner(), new.read_access(), new.write_access());
+        if old_access != new_access {
+            let breaking = old.owner() != new.owner()
+                || (old.read_access().is_read() && !new.read_access().is_read())
+                || (old.write_access().is_write() && !new.write_access().is_write());
+            self.push(
+                ChangeKind::Access,
+                name.to_string(),
+                Some(fmt_access(old_access)),
+                Some(fmt_access(new_access)),
+                breaking,
+            );
+        }
+
+        if old.reserved() != new.reserved() {
+            self.push(
+                ChangeKind::Access,
+                name.to_string(),
+                Some(fmt_reserved(old.reserved())),
+                Some(fmt_reserved(new.reserved())),
+                new.reserved().is_some(),
+            );
+        }
+
+        // Register default is only relevant without fields, otherwise fields defaults are compared
+        if old.default() != new.default() && (old.field().is_none() || new.field().is_none()) {
+            self.push(
+                ChangeKind::Default,
+                name.to_string(),
+                Some(fmt_default(Some(old.default()))),
+                Some(fmt_default(Some(new.default()))),
+                false,
+            );
+        }
+
+        let no_field = Vec::new();
+        let old_fields = old.field().as_ref().unwrap_or(&no_field);
+        let new_fields = new.field().as_ref().unwrap_or(&no_field);
+        for field in old_fields.iter() {
+            let element = format!("{name}::{}", field.name());
+            match new_fields.iter().find(|f| f.name() == field.name()) {
+                Some(new_field) => self.field(&element, field, new_field),
+                None => self.push(
+                    ChangeKind::Removed,
+                    element,
+                    Some(fmt_bits(field)),
+                    None,
+                    true,
+                ),
+            }
+        }
+        for field in new_fields.iter() {
+            if !old_fields.iter().any(|f| f.name() == field.name()) {
+                self.push(
+                    ChangeKind::Added,
+                    format!("{name}::{}", field.name()),
+                    None,
+                    Some(fmt_bits(field)),
+                    false,
+                );
+            }
+        }
+    }
+
+    /// Compare two versions of the same field
+    /// NB: Position and width are checked independently, a moved field could also be resized
+    fn field(&mut self, element: &str, old: &Field, new: &Field) {
+        if old.offset_b() != new.offset_b() {
+            self.push(
+                ChangeKind::Moved,
+                element.to_string(),
+                Some(fmt_bits(old)),
+                Some(fmt_bits(new)),
+                true,
+            );
+        }
+        if old.size_b() != new.size_b() {
+            // NB: A wider field keeps the old bits at the same position, a narrower one truncates them
+            self.push(
+                ChangeKind::Resized,
+                element.to_string(),
+                Some(fmt_bits(old)),
+                Some(fmt_bits(new)),
+                new.size_b() < old.size_b(),
+            );
+        }
+        if old.default() != new.default() {
+            self.push(
+                ChangeKind::Default,
+                element.to_string(),
+                Some(fmt_default(old.default().as_ref())),
+                Some(fmt_default(new.default().as_ref())),
+                false,
+            );
+        }
+        // NB: Reserving removes the bits from the software view, releasing them is transparent
+        if old.reserved() != new.reserved() {
+            self.push(
+                ChangeKind::Access,
+                element.to_string(),
+                Some(fmt_reserved(old.reserved())),
+                Some(fmt_reserved(new.reserved())),
+                new.reserved().is_some(),
+            );
+        }
+    }
+}
+
+impl std::fmt::Display for RegmapDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in self.changes.iter() {
+            writeln!(f, "{change}")?;
+        }
+        write!(
+            f,
+            "{} change{} found, {} breaking",
+            self.changes.len(),
+            if self.changes.len() != 1 { "s" } else { "" },
+            self.breaking().count()
+        )
+    }
+}
+
+/// List registers with their `section::register` name
+/// NB: Registers of child blocks are prefixed by the block instance path
+fn registers(regmap: &Regmap) -> Vec<(String, &Register)> {
+    regmap
+        .all_sections()
+        .into_iter()
+        .flat_map(|(sec_name, sec)| {
+            sec.register()
+                .iter()
+                .map(move |reg| (format!("{sec_name}::{}", reg.name()), reg))
+        })
+        .collect()
+}
+
+fn fmt_access(access: (&Owner, &ReadAccess, &WriteAccess)) -> String {
+    format!("{:?}/{:?}/{:?}", access.0, access.1, access.2)
+}
+
+fn fmt_reserved(reserved: &Option<Reserved>) -> String {
+    match reserved {
+        Some(policy) => format!("reserved {policy}"),
+        None => "used".to_string(),
+    }
+}
+
+fn fmt_default(dflt: Option<&DefaultVal>) -> String {
+    match dflt {
+        None => "none".to_string(),
+        Some(DefaultVal::Cst(val)) => format!("0x{val:x}"),
+        Some(DefaultVal::Param(param)) => param.clone(),
+        Some(dflt) => format!("{dflt:?}"),
+    }
+}
+
+fn fmt_bits(field: &Field) -> String {
+    // NB: Guard against empty field, msb would underflow
+    match field.size_b() {
+        0 => format!("[empty@{}]", field.offset_b()),
+        size_b => format!("[{}:{}]", field.offset_b() + size_b - 1, field.offset_b()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::regmap::parser::RegmapOpt;
+
+    /// Elaborate a single register with the given field definition
+    fn regmap(field: &str) -> Regmap {
+        let content = format!(
+            r#"
+module_name="test"
+description="Unit test regmap"
+word_size_b=32
+offset=0x0
+range=0x100
+ext_pkg=[]
+[section.A]
+description="a"
+[section.A.register.R]
+description="r"
+owner="User"
+read_access="Read"
+write_access="Write"
+field.f={field}
+"#
+        );
+        let mut opt = RegmapOpt::from_toml_str("test.toml", &content).expect("Valid toml snippet");
+        Regmap::from_opt(std::slice::from_mut(&mut opt)).expect("Valid regmap")
+    }
+
+    #[test]
+    fn moved_and_resized_field() {
+        let old = regmap(r#"{size_b=8, offset_b=0, description="f"}"#);
+        let new = regmap(r#"{size_b=4, offset_b=8, description="f"}"#);
+        let diff = RegmapDiff::new(&old, &new);
+        let kinds = diff.changes.iter().map(|c| c.kind).collect::<Vec<_>>();
+        assert_eq!(kinds, [ChangeKind::Moved, ChangeKind::Resized]);
+        assert!(diff.changes.iter().all(|c| c.breaking));
+        assert_eq!(diff.changes[1].old.as_deref(), Some("[7:0]"));
+        assert_eq!(diff.changes[1].new.as_deref(), Some("[11:8]"));
+    }
+}
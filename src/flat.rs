@@ -1,37 +1,59 @@
 //!
 //! Provide a flatten view of register map
 //! Useful on the Sw side to easily access register with offset and description
+//! NB: Part of the runtime, built from the json interchange format without the generator
 
+use crate::error::RegmapError;
+use crate::model::{JsonDoc, JsonRegmap, Reserved, field_mask};
+#[cfg(feature = "generator")]
+use crate::regmap::ElabOpt;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
 use getset::Getters;
-use std::collections::HashMap;
+use serde::Serialize;
 
-#[derive(Debug, Getters)]
+#[derive(Debug, Getters, Serialize)]
 #[getset(get = "pub")]
 pub struct FlatField {
     name: String,
     description: String,
+    /// Actual width, parameterized field fallback to its maximum width without parameter value
     size_b: usize,
     offset_b: usize,
+    /// Reset value, None if it relies on a parameter without value
+    reset: Option<u32>,
+    /// Reserved bits couldn't be written with `FlatRegister::from_field`, except PreserveOnWrite
+    /// ones which are written back as read
+    reserved: Option<Reserved>,
 }
-impl std::fmt::Display for FlatField {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FlatField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{:-<40}", self.name)?;
         writeln!(f, "field: {}", self.description)?;
         writeln!(f, "size_b: {}", self.size_b)?;
-        writeln!(f, "offset_b: {}", self.size_b)?;
+        writeln!(f, "offset_b: {}", self.offset_b)?;
+        if let Some(reset) = self.reset {
+            writeln!(f, "reset: 0x{reset:x}")?;
+        }
+        if let Some(policy) = self.reserved {
+            writeln!(f, "reserved: {policy}")?;
+        }
         Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub enum Access {
     None,
     Read,
     Write,
     ReadWrite,
 }
-impl std::fmt::Display for Access {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let frag = match self {
             Access::None => "--",
             Access::Read => "Ro",
@@ -42,7 +64,7 @@ impl std::fmt::Display for Access {
     }
 }
 
-#[derive(Debug, Getters)]
+#[derive(Debug, Getters, Serialize)]
 #[getset(get = "pub")]
 pub struct FlatRegister {
     // Section info
@@ -53,17 +75,27 @@ pub struct FlatRegister {
     reg_description: String,
     access: Access,
     offset: usize,
+    /// Reset value, None if it relies on a parameter without value
+    reset: Option<u32>,
+    /// Reserved register couldn't be written with `FlatRegister::from_field`
+    reserved: Option<Reserved>,
     // Field info
     field: Vec<FlatField>,
 }
-impl std::fmt::Display for FlatRegister {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FlatRegister {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "{:-<80}", self.reg_name)?;
         writeln!(f, "section: {}", self.sec_name)?;
         writeln!(f, "section descr.: {}", self.sec_description)?;
         writeln!(f, "register descr.: {}", self.reg_description)?;
         writeln!(f, "access: {}", self.access)?;
         writeln!(f, "offset: 0x{:x}", self.offset)?;
+        if let Some(reset) = self.reset {
+            writeln!(f, "reset: 0x{reset:x}")?;
+        }
+        if let Some(policy) = self.reserved {
+            writeln!(f, "reserved: {policy}")?;
+        }
         for field in self.field.iter() {
             write!(f, "{field}")?;
         }
@@ -72,7 +104,7 @@ impl std::fmt::Display for FlatRegister {
 }
 
 impl FlatRegister {
-    pub fn as_field(&self, value: u32) -> HashMap<String, u32> {
+    pub fn as_field(&self, value: u32) -> BTreeMap<String, u32> {
         self.field
             .iter()
             .map(
@@ -82,14 +114,27 @@ impl FlatRegister {
                      offset_b,
                      ..
                  }| {
-                    let field_value = (value >> offset_b) & ((1 << size_b) - 1);
+                    let field_value = (value >> offset_b) & field_mask(*size_b) as u32;
                     (name.clone(), field_value)
                 },
             )
-            .collect::<HashMap<_, _>>()
+            .collect::<BTreeMap<_, _>>()
     }
 
-    pub fn from_field(&self, field: HashMap<&str, u32>) -> u32 {
+    /// Build register value from a set of field values
+    /// Error is returned if the register is reserved, if a field is unknown or reserved or if a
+    /// value doesn't fit in its field
+    /// NB: PreserveOnWrite fields are accepted, the caller gives back the value it read
+    /// NB: Any list of (name, value) pairs is accepted (e.g. HashMap, BTreeMap or array)
+    pub fn from_field<'a>(
+        &self,
+        field: impl IntoIterator<Item = (&'a str, u32)>,
+    ) -> Result<u32, RegmapError> {
+        let register = format!("{}::{}", self.sec_name, self.reg_name);
+        if let Some(policy) = self.reserved {
+            return Err(RegmapError::ReservedRegister { register, policy });
+        }
+
         let fields_map = self
             .field
             .iter()
@@ -98,20 +143,39 @@ impl FlatRegister {
                      name,
                      size_b,
                      offset_b,
+                     reserved,
                      ..
-                 }| (name, (size_b, offset_b)),
+                 }| (name.as_str(), (size_b, offset_b, reserved)),
             )
-            .collect::<HashMap<_, _>>();
+            .collect::<BTreeMap<_, _>>();
 
-        field
-            .iter()
-            .map(|(name, val)| {
-                let (size_b, offset_b) = fields_map
-                    .get(&name.to_string())
-                    .unwrap_or_else(|| panic!("Field {name} isn't available in {:?}", self));
-                (val & ((1 << *size_b) - 1)) << *offset_b
-            })
-            .sum()
+        field.into_iter().try_fold(0, |acc, (name, val)| {
+            let (size_b, offset_b, reserved) =
+                fields_map
+                    .get(name)
+                    .ok_or_else(|| RegmapError::UnknownField {
+                        register: register.clone(),
+                        field: name.to_string(),
+                    })?;
+            match reserved {
+                None | Some(Reserved::PreserveOnWrite) => {}
+                Some(policy) => {
+                    return Err(RegmapError::ReservedField {
+                        register: register.clone(),
+                        field: name.to_string(),
+                        policy: *policy,
+                    });
+                }
+            }
+            if (val & !(field_mask(**size_b) as u32)) != 0 {
+                return Err(RegmapError::ValueOverflow {
+                    field: name.to_string(),
+                    value: val,
+                    size_b: **size_b,
+                });
+            }
+            Ok(acc | (val << *offset_b))
+        })
     }
 }
 
@@ -120,10 +184,13 @@ impl FlatRegister {
 pub struct FlatRegmap {
     offset: usize,
     range: usize,
-    register: HashMap<String, FlatRegister>,
+    /// Registers by hash name, sorted by name
+    /// NB: BTreeMap instead of the former HashMap to build `no_std`, this breaks callers naming
+    /// the map type
+    register: BTreeMap<String, FlatRegister>,
 }
-impl std::fmt::Display for FlatRegmap {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl fmt::Display for FlatRegmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for (hash_name, reg) in self.register.iter() {
             writeln!(f, "{:-<120}", hash_name)?;
             writeln!(f, "{reg}")?;
@@ -133,38 +200,91 @@ impl std::fmt::Display for FlatRegmap {
 }
 
 impl FlatRegmap {
-    pub fn from_file(regmap_toml: &[&str]) -> Self {
+    /// Build the flatten view from toml definitions
+    /// NB: Stop on the first reported error, use `Regmap::check_files` to retrieved all of them
+    #[cfg(feature = "generator")]
+    pub fn from_file(regmap_toml: &[&str]) -> Result<Self, RegmapError> {
+        Self::from_file_with(regmap_toml, &ElabOpt::default())
+    }
+
+    /// Build the flatten view with custom elaboration options
+    /// NB: Reset values and field widths relying on RTL parameters are only available with
+    /// `elab.params`
+    #[cfg(feature = "generator")]
+    pub fn from_file_with(regmap_toml: &[&str], elab: &ElabOpt) -> Result<Self, RegmapError> {
         // Parse regmap with optional fields
         let mut regmap_list = regmap_toml
             .iter()
             .map(|toml| crate::RegmapOpt::read_from(toml))
-            .collect::<Vec<_>>();
+            .collect::<Result<Vec<_>, _>>()?;
 
         // Expand fields and check to have concrete regmap
-        let regmap = crate::Regmap::from_opt(&mut regmap_list).unwrap();
-        Self::new(regmap)
+        let regmap = crate::Regmap::from_opt_with(&mut regmap_list, elab)?;
+        Ok(Self::new(regmap))
     }
 
+    /// Retrieved register from its hash name (i.e. section::register or block::section::register)
+    pub fn get_register(&self, name: &str) -> Result<&FlatRegister, RegmapError> {
+        self.register
+            .get(name)
+            .ok_or_else(|| RegmapError::UnknownRegister {
+                name: name.to_string(),
+            })
+    }
+
+    /// Build the flatten view from the json interchange format (i.e. output of the `json`
+    /// generator), the toml definition isn't required
+    /// NB: file is only used to give context in error message
+    pub fn from_json_str(file: &str, content: &str) -> Result<Self, RegmapError> {
+        JsonDoc::from_json_str(file, content).map(|doc| Self::from_model(&doc.regmap))
+    }
+
+    /// Read the flatten view from a json interchange file
+    #[cfg(feature = "std")]
+    pub fn from_json_file(file: &str) -> Result<Self, RegmapError> {
+        JsonDoc::read_from(file).map(|doc| Self::from_model(&doc.regmap))
+    }
+
+    #[cfg(feature = "generator")]
     pub fn new(regmap: crate::Regmap) -> Self {
-        let mut register = HashMap::new();
-        regmap.section().iter().for_each(|sec| {
-            sec.register().iter().for_each(|reg| {
-                let hash_name = format!("{}::{}", sec.name(), reg.name());
-                let field = if let Some(fmap) = reg.field() {
-                    let mut field = Vec::new();
-                    fmap.iter().for_each(|f| {
-                        field.push(FlatField {
-                            name: f.name().clone(),
-                            description: f.description().clone(),
-                            size_b: *f.size_b(),
-                            offset_b: *f.offset_b(),
-                        });
-                    });
-                    field
-                } else {
-                    vec![]
-                };
-                let access = match (reg.read_access().is_read(), reg.write_access().is_write()) {
+        // NB: Built through the json model, thus both sources give the same view
+        Self::from_model(&JsonRegmap::from(&regmap))
+    }
+
+    fn from_model(regmap: &JsonRegmap) -> Self {
+        let mut register = BTreeMap::new();
+        Self::insert_registers(regmap, "", &mut register);
+        Self {
+            register,
+            offset: regmap.offset,
+            range: regmap.range,
+        }
+    }
+
+    /// Insert the registers of a regmap and recursively of its blocks
+    /// NB: Registers of child blocks are prefixed by the block instance path
+    fn insert_registers(
+        regmap: &JsonRegmap,
+        prefix: &str,
+        register: &mut BTreeMap<String, FlatRegister>,
+    ) {
+        for sec in regmap.section.iter() {
+            let sec_name = format!("{prefix}{}", sec.name);
+            for reg in sec.register.iter() {
+                let hash_name = format!("{sec_name}::{}", reg.name);
+                let field = reg
+                    .field
+                    .iter()
+                    .map(|f| FlatField {
+                        name: f.name.clone(),
+                        description: f.description.clone(),
+                        size_b: f.width.unwrap_or(f.size_b),
+                        offset_b: f.offset_b,
+                        reset: f.reset.map(|val| val as u32),
+                        reserved: f.reserved,
+                    })
+                    .collect();
+                let access = match (reg.read_access.is_read(), reg.write_access.is_write()) {
                     (false, false) => Access::None,
                     (false, true) => Access::Write,
                     (true, false) => Access::Read,
@@ -173,21 +293,91 @@ impl FlatRegmap {
                 register.insert(
                     hash_name,
                     FlatRegister {
-                        sec_name: sec.name().clone(),
-                        sec_description: sec.description().clone(),
-                        reg_name: reg.name().clone(),
-                        reg_description: reg.description().clone(),
+                        sec_name: sec_name.clone(),
+                        sec_description: sec.description.clone(),
+                        reg_name: reg.name.clone(),
+                        reg_description: reg.description.clone(),
                         access,
-                        offset: *reg.offset(),
+                        offset: reg.offset,
+                        reset: reg.reset.map(|val| val as u32),
+                        reserved: reg.reserved,
                         field,
                     },
                 );
-            });
-        });
-        Self {
-            register,
-            offset: *regmap.offset(),
-            range: *regmap.range(),
+            }
+        }
+        for blk in regmap.block.iter() {
+            Self::insert_registers(&blk.regmap, &format!("{prefix}{}::", blk.name), register);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn field(name: &str, size_b: usize, offset_b: usize, reserved: Option<Reserved>) -> FlatField {
+        FlatField {
+            name: name.to_string(),
+            description: String::new(),
+            size_b,
+            offset_b,
+            reset: Some(0),
+            reserved,
+        }
+    }
+
+    fn register(reserved: Option<Reserved>, field: Vec<FlatField>) -> FlatRegister {
+        FlatRegister {
+            sec_name: "Ctrl".to_string(),
+            sec_description: String::new(),
+            reg_name: "Config".to_string(),
+            reg_description: String::new(),
+            access: Access::ReadWrite,
+            offset: 0,
+            reset: Some(0),
+            reserved,
+            field,
         }
     }
+
+    #[test]
+    fn reserved_field() {
+        let reg = register(
+            None,
+            vec![
+                field("mode", 4, 0, None),
+                field("rsvd", 4, 4, Some(Reserved::ReadAsZero)),
+            ],
+        );
+        assert_eq!(reg.from_field([("mode", 0x3)]).unwrap(), 0x3);
+        let err = reg.from_field([("mode", 0x3), ("rsvd", 0x1)]).unwrap_err();
+        assert_eq!(err.kind(), "ReservedField");
+    }
+
+    #[test]
+    fn reserved_register() {
+        // NB: Refused even without fields, or with fields without policy of their own
+        let reg = register(Some(Reserved::WriteIgnored), vec![]);
+        assert_eq!(reg.from_field([]).unwrap_err().kind(), "ReservedRegister");
+        let reg = register(Some(Reserved::ReadAsZero), vec![field("mode", 4, 0, None)]);
+        let err = reg.from_field([("mode", 0x1)]).unwrap_err();
+        assert_eq!(err.kind(), "ReservedRegister");
+    }
+
+    #[test]
+    fn preserve_on_write_field() {
+        let reg = register(
+            None,
+            vec![
+                field("mode", 4, 0, None),
+                field("vendor", 8, 16, Some(Reserved::PreserveOnWrite)),
+            ],
+        );
+        let val = reg.from_field([("mode", 0x3), ("vendor", 0xa5)]).unwrap();
+        assert_eq!(val, 0x00a5_0003);
+        let err = reg.from_field([("vendor", 0x1ff)]).unwrap_err();
+        assert_eq!(err.kind(), "ValueOverflow");
+    }
 }
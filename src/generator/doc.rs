@@ -0,0 +1,136 @@
+//!
+//! Documentation generators
+//! Regmap is described in Markdown for online documentation and in Json for tools
+//!
+
+use serde::Serialize;
+
+use super::{GenCtx, GenFile, Generator, Scope};
+use crate::regmap::{Regmap, Section};
+
+/// Section with its hierarchical name (i.e. `block::section`)
+#[derive(Debug, Serialize)]
+struct DocSection<'a> {
+    path: String,
+    #[serde(flatten)]
+    section: &'a Section,
+}
+
+/// Child block with its depth in the hierarchy
+#[derive(Debug, Serialize)]
+struct DocBlock {
+    path: String,
+    depth: usize,
+    module_name: String,
+    description: String,
+    offset: usize,
+    range: usize,
+}
+
+/// Flatten the block tree in depth-first order
+fn doc_blocks(regmap: &Regmap, prefix: &str, depth: usize, blocks: &mut Vec<DocBlock>) {
+    for blk in regmap.block().iter() {
+        let path = format!("{prefix}{}", blk.name());
+        blocks.push(DocBlock {
+            path: path.clone(),
+            depth,
+            module_name: blk.regmap().module_name().clone(),
+            description: blk.description().clone(),
+            offset: *blk.offset(),
+            range: *blk.range(),
+        });
+        doc_blocks(blk.regmap(), &format!("{path}::"), depth + 1, blocks);
+    }
+}
+
+/// Generate a structured document targeting online documentation
+pub struct MarkdownGen;
+
+impl Generator for MarkdownGen {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn description(&self) -> &str {
+        "Markdown documentation"
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        vec![format!("{}_doc.md", regmap.module_name())]
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        // NB: Sections of the child blocks are documented with their hierarchical name
+        let sections = regmap
+            .all_sections()
+            .into_iter()
+            .map(|(path, section)| DocSection { path, section })
+            .collect::<Vec<_>>();
+        let mut blocks = Vec::new();
+        doc_blocks(regmap, "", 0, &mut blocks);
+
+        let mut context = ctx.tera_context();
+        context.insert("regmap", &regmap);
+        context.insert("sections", &sections);
+        context.insert("blocks", &blocks);
+        let md_rendered = ctx.tera.render("docs/fmt_as.md", &context)?;
+
+        Ok(vec![GenFile {
+            path: self.outputs(regmap).remove(0),
+            content: md_rendered,
+        }])
+    }
+}
+
+/// Serialize the regmap in the versioned json interchange format (cf. regmap::json)
+pub struct JsonGen;
+
+impl Generator for JsonGen {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn description(&self) -> &str {
+        "Json description of the elaborated regmap"
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        vec![format!("{}_doc.json", regmap.module_name())]
+    }
+
+    fn generate(&self, regmap: &Regmap, _ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        Ok(vec![GenFile {
+            path: self.outputs(regmap).remove(0),
+            content: regmap.to_json(),
+        }])
+    }
+}
+
+/// Export the elaborated regmap in TOML with all the inferred properties explicit
+/// Used to review and freeze a layout
+pub struct ElaboratedTomlGen;
+
+impl Generator for ElaboratedTomlGen {
+    fn name(&self) -> &str {
+        "toml"
+    }
+
+    fn description(&self) -> &str {
+        "Elaborated TOML with explicit offsets, ranges and alignments"
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Slice
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        vec![format!("{}_elaborated.toml", regmap.module_name())]
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        Ok(vec![GenFile {
+            path: self.outputs(regmap).remove(0),
+            content: regmap.to_toml(ctx.output_path),
+        }])
+    }
+}
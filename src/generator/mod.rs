@@ -0,0 +1,251 @@
+//!
+//! Output generators
+//! Each generator converts an elaborated Regmap in a set of files (e.g. RTL, documentation, Sw headers).
+//! Generators are gathered in a registry and selected by name. Third-party generators could be
+//! registered through the library API.
+//!
+
+pub mod doc;
+pub mod sv;
+pub mod sw;
+pub mod templates;
+
+pub use templates::tera_engine;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use regex::Regex;
+use tera::Tera;
+
+use crate::regmap::Regmap;
+
+/// Regmap on which a generator is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Applied once on the fused regmap (i.e. aggregation of all the toml files)
+    Fused,
+    /// Applied on each toml file individually
+    Slice,
+}
+
+/// Generated file
+/// Path is relative to the output folder
+#[derive(Debug, Clone)]
+pub struct GenFile {
+    pub path: String,
+    pub content: String,
+}
+
+/// Context shared by all the generators
+pub struct GenCtx<'a> {
+    /// Templating engine with all the available templates
+    pub tera: &'a Tera,
+    /// Version of the tool, stamped in generated files
+    pub tool_version: &'a str,
+    /// Output folder, paths written in generated files are relative to it
+    pub output_path: &'a Path,
+}
+
+impl GenCtx<'_> {
+    /// Create a tera context with the common entries
+    pub fn tera_context(&self) -> tera::Context {
+        let mut context = tera::Context::new();
+        context.insert("tool_version", self.tool_version);
+        context
+    }
+}
+
+/// Output generator
+pub trait Generator {
+    /// Name used to select the generator (i.e. `--emit <name>`)
+    fn name(&self) -> &str;
+
+    /// Short description of the generated outputs
+    fn description(&self) -> &str;
+
+    /// Regmap on which the generator is applied
+    fn scope(&self) -> Scope {
+        Scope::Fused
+    }
+
+    /// Set of files generated for the given regmap (relative to the output folder)
+    fn outputs(&self, regmap: &Regmap) -> Vec<String>;
+
+    /// Render the outputs of the given regmap
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error>;
+}
+
+/// Set of available generators
+/// Generators are kept in registration order
+pub struct GeneratorRegistry {
+    generators: IndexMap<String, Box<dyn Generator>>,
+}
+
+impl Default for GeneratorRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Box::new(sv::SvModuleGen));
+        registry.register(Box::new(sv::SvPkgGen));
+        registry.register(Box::new(sv::SvInterconnectGen));
+        registry.register(Box::new(doc::MarkdownGen));
+        registry.register(Box::new(doc::JsonGen));
+        registry.register(Box::new(doc::ElaboratedTomlGen));
+        registry.register(Box::new(sw::CHeaderGen));
+        registry.register(Box::new(sw::RustGen));
+        registry
+    }
+}
+
+impl GeneratorRegistry {
+    /// Empty registry
+    /// Use `default()` to get a registry with all the built-in generators
+    pub fn new() -> Self {
+        Self {
+            generators: IndexMap::new(),
+        }
+    }
+
+    /// Register a generator
+    /// NB: A generator with the same name is replaced
+    pub fn register(&mut self, generator: Box<dyn Generator>) {
+        self.generators
+            .insert(generator.name().to_string(), generator);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Generator> {
+        self.generators.get(name).map(|g| g.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.generators.keys().map(|k| k.as_str()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Generator> {
+        self.generators.values().map(|g| g.as_ref())
+    }
+
+    /// Resolve a list of generator names
+    /// Fail on the first unknown name with the list of available generators
+    pub fn select(&self, emit: &[String]) -> Result<Vec<&dyn Generator>, anyhow::Error> {
+        emit.iter()
+            .map(|name| {
+                self.get(name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown generator `{name}` [available: {}]",
+                        self.names().join(", ")
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Run the selected generators and write their outputs in `ctx.output_path`
+    /// Fused generators are applied on `fused`, slice generators on each entry of `slices`
+    /// Return the list of written files
+    pub fn run(
+        &self,
+        emit: &[String],
+        fused: &Regmap,
+        slices: &[Regmap],
+        ctx: &GenCtx,
+    ) -> Result<Vec<PathBuf>, anyhow::Error> {
+        // Check that all generators are known before writing anything
+        let generators = self.select(emit)?;
+
+        std::fs::create_dir_all(ctx.output_path)?;
+        let mut written = Vec::new();
+        for generator in generators {
+            let targets = match generator.scope() {
+                Scope::Fused => std::slice::from_ref(fused),
+                Scope::Slice => slices,
+            };
+            for regmap in targets {
+                for file in generator.generate(regmap, ctx)? {
+                    let path = ctx.output_path.join(&file.path);
+                    std::fs::write(&path, file.content).map_err(|err| {
+                        anyhow::anyhow!("Unable to write file {}: {err}", path.display())
+                    })?;
+                    written.push(path);
+                }
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Simple post-process of String generated by templating engine
+/// Templating introduce a lot of consecutive newlines due to filtering
+/// -> This function remove consecutive newline to have something more readable in the end
+pub fn post_process(raw: &str) -> String {
+    // Remove extra new-line generated by tera templating
+    let regex = Regex::new(r"\s*\n\s*\n+").unwrap();
+    let post_rendered = regex.replace_all(raw, "\n");
+
+    post_rendered.to_string()
+}
+
+/// Simple function used to render integer in hexadecimal format with tera
+/// Syntax in tera file is: as_hex()
+pub fn as_hex(args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+    // Extract width if specified
+    let width = if let Some(width) = args.get("width") {
+        if let tera::Value::Number(num) = width {
+            Ok(num.as_u64().unwrap() as usize)
+        } else {
+            Err(tera::Error::msg("Width is not an integer"))
+        }
+    } else {
+        Ok(0)
+    }?;
+
+    if let Some(value) = args.get("val") {
+        if let tera::Value::Number(num) = value {
+            let hex_str = format!("0x{:0>width$x}", num.as_u64().unwrap(), width = width);
+            Ok(tera::Value::String(hex_str))
+        } else {
+            Err(tera::Error::msg("Value is not an integer"))
+        }
+    } else {
+        Err(tera::Error::msg(
+            "Function `as_hex` didn't receive a `val` argument",
+        ))
+    }
+}
+
+/// Simple function used to render integer in hexadecimal SystemVerilog format with tera
+/// Syntax in tera file is: as_sv_hex()
+pub fn as_sv_hex(args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
+    // Extract width if specified
+    let width = if let Some(width) = args.get("width") {
+        if let tera::Value::Number(num) = width {
+            Ok(Some(num.as_u64().unwrap() as usize))
+        } else {
+            Err(tera::Error::msg("Width is not an integer"))
+        }
+    } else {
+        Ok(None)
+    }?;
+
+    if let Some(value) = args.get("val") {
+        if let tera::Value::Number(num) = value {
+            let hex_str = format!(
+                "{}'h{:x}",
+                if let Some(w) = width {
+                    format!("{w}")
+                } else {
+                    "".to_string()
+                },
+                num.as_u64().unwrap(),
+            );
+            Ok(tera::Value::String(hex_str))
+        } else {
+            Err(tera::Error::msg("Value is not an integer"))
+        }
+    } else {
+        Err(tera::Error::msg(
+            "Function `as_sv_hex` didn't receive a `val` argument",
+        ))
+    }
+}
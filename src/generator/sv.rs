@@ -0,0 +1,912 @@
+//!
+//! SystemVerilog generators
+//! Regmap is converted in a RTL module and a package with address/field definitions
+//!
+//! A regmap with child blocks is also wrapped in a `<module>_top` module that instantiates the
+//! local registers and the child modules behind an address decoder.
+//!
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use tera::Tera;
+
+use super::{GenCtx, GenFile, Generator, Scope, post_process};
+use crate::model::field_mask;
+use crate::regmap::block::Block;
+use crate::regmap::parser::{Owner, ReadAccess, Reserved, WriteAccess};
+use crate::regmap::{DefaultVal, Register, Regmap, Section};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SvRegister {
+    name: String,
+    param_snippets: String,
+    io_snippets: String,
+    default_snippets: String,
+    rd_snippets: String,
+    ff_wr_snippets: String,
+    check_snippets: String,
+}
+
+/// Parameter used as default value
+/// Its value is checked at RTL elaboration against the width of its target
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ParamCheck {
+    param: String,
+    target: String,
+    label: String,
+    width: usize,
+    max: u64,
+}
+
+impl ParamCheck {
+    /// List parameters used by register default that must be checked
+    /// NB: Parameters are SystemVerilog int -> Only target narrower than 32b must be checked
+    fn from_register(full_name: &str, word_w: usize, register: &Register) -> Vec<Self> {
+        let mut targets = Vec::new();
+        match register.default() {
+            DefaultVal::Param(p) => targets.push((p.clone(), full_name.to_string(), word_w)),
+            DefaultVal::ParamsField { .. } => {
+                if let Some(fields) = register.field() {
+                    for f in fields {
+                        if let Some(DefaultVal::Param(p)) = f.default() {
+                            targets.push((
+                                p.clone(),
+                                format!("{full_name}.{}", f.name()),
+                                *f.size_b(),
+                            ));
+                        }
+                    }
+                }
+            }
+            DefaultVal::Cst(_) => {}
+        }
+
+        targets
+            .into_iter()
+            .filter(|(_, _, width)| *width < i32::BITS as usize)
+            .map(|(param, target, width)| Self {
+                label: target.replace('.', "_"),
+                param,
+                target,
+                width,
+                max: (1_u64 << width) - 1,
+            })
+            .collect()
+    }
+}
+
+/// Field sized by a parameter
+/// Its value is checked at RTL elaboration against the field maximum width and bits above it are
+/// masked on write
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WidthParam {
+    param: String,
+    target: String,
+    label: String,
+    offset: usize,
+    max: usize,
+}
+
+impl WidthParam {
+    fn from_register(full_name: &str, register: &Register) -> Vec<Self> {
+        register
+            .field()
+            .iter()
+            .flat_map(|fields| fields.iter())
+            .filter_map(|f| {
+                f.size_param().as_ref().map(|param| Self {
+                    param: param.clone(),
+                    target: format!("{full_name}.{}", f.name()),
+                    label: format!("{full_name}_{}", f.name()),
+                    offset: *f.offset_b(),
+                    max: *f.size_b(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl SvRegister {
+    pub fn from_register(
+        section_name: &str,
+        word_w: &usize,
+        register: &Register,
+        used_params: &mut Vec<String>,
+        param_value: &IndexMap<String, usize>,
+        tera: &Tera,
+    ) -> Self {
+        let mut context = tera::Context::new();
+        let full_name = format!("{section_name}_{}", register.name());
+        let mut cst_name = format!("{section_name}_{}_OFS", register.name());
+        cst_name.make_ascii_uppercase();
+        context.insert("name", &full_name);
+        context.insert("offset_cst_name", &cst_name);
+        let mut dflt_name = register.params_list();
+        // Filter duplication in param_name.
+        // NB: A parameters used by multiple reg must appear only once at top level
+        // -> Retain only params not already in use and update the in-use list
+        dflt_name.retain(|e| !used_params.contains(e));
+        used_params.extend(dflt_name.clone());
+
+        context.insert("default_name", &dflt_name);
+        // NB: Width parameters without value default to the field maximum width
+        let mut param_value = param_value.clone();
+        for (param, max) in register.size_params() {
+            param_value.entry(param).or_insert(max);
+        }
+        context.insert("param_value", &param_value);
+        context.insert("default_val", register.default());
+        // Expand Owner/Mode to ease tera templating
+        // NB: Read-as-zero and write-ignored registers are constant, as parameter registers
+        context.insert(
+            "param_reg",
+            &(matches!(register.owner(), Owner::Parameter)
+                || matches!(
+                    register.reserved(),
+                    Some(Reserved::ReadAsZero | Reserved::WriteIgnored)
+                )),
+        );
+        context.insert("reserved", &register.reserved().map(|p| p.to_string()));
+        context.insert("reg_update", &matches!(register.owner(), Owner::Kernel));
+        context.insert(
+            "wr_user",
+            &match register.owner() {
+                Owner::User => register.write_access() != &WriteAccess::None,
+                _ => false,
+            },
+        );
+        context.insert(
+            "rd_notify",
+            &matches!(register.read_access(), ReadAccess::ReadNotify),
+        );
+        context.insert(
+            "wr_notify",
+            &matches!(register.write_access(), WriteAccess::WriteNotify),
+        );
+
+        context.insert("have_fields", &register.field().is_some());
+        context.insert(
+            "param_checks",
+            &ParamCheck::from_register(&full_name, *word_w, register),
+        );
+        let mut mask_name = format!("{full_name}_WR_MASK");
+        mask_name.make_ascii_uppercase();
+        context.insert("mask_name", &mask_name);
+        context.insert(
+            "width_params",
+            &WidthParam::from_register(&full_name, register),
+        );
+        // Reserved bits: read-as-zero and write-ignored ones aren't written
+        let rsvd_mask = |policies: &[Reserved]| {
+            register
+                .field()
+                .iter()
+                .flatten()
+                .filter(|f| f.reserved().is_some_and(|p| policies.contains(&p)))
+                .fold(0_u64, |acc, f| {
+                    acc | (field_mask(*f.size_b()) << f.offset_b())
+                })
+        };
+        context.insert(
+            "rsvd_mask",
+            &rsvd_mask(&[Reserved::ReadAsZero, Reserved::WriteIgnored]),
+        );
+        context.insert("raz_mask", &rsvd_mask(&[Reserved::ReadAsZero]));
+        context.insert("wi_mask", &rsvd_mask(&[Reserved::WriteIgnored]));
+        for (key, suffix) in [
+            ("rsvd_name", "RSVD_MASK"),
+            ("raz_name", "RAZ_MASK"),
+            ("wi_name", "WI_MASK"),
+        ] {
+            context.insert(key, &format!("{full_name}_{suffix}").to_ascii_uppercase());
+        }
+
+        // Render Param section
+        // NB: Trim \n at end to prevent double comma insertion
+        let raw_param_snippets = tera.render("module/param.sv", &context).unwrap();
+        let param_snippets = raw_param_snippets.trim_end_matches("\n").to_string();
+
+        // Render Io section
+        // NB: Reserved registers have no port
+        let io_snippets = match (register.owner(), register.reserved()) {
+            (Owner::Parameter, _) | (_, Some(_)) => String::new(),
+            _ => tera.render("module/io.sv", &context).unwrap(),
+        };
+
+        let default_snippets = tera.render("module/default.sv", &context).unwrap();
+
+        let ff_wr_snippets = tera.render("module/write.sv", &context).unwrap();
+
+        let check_snippets = tera.render("module/check.sv", &context).unwrap();
+
+        let rd_snippets = match register.read_access() {
+            ReadAccess::None => String::new(),
+            ReadAccess::Read | ReadAccess::ReadNotify => {
+                tera.render("module/read.sv", &context).unwrap()
+            }
+        };
+        Self {
+            name: full_name,
+            param_snippets,
+            io_snippets,
+            default_snippets,
+            rd_snippets,
+            ff_wr_snippets,
+            check_snippets,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SvRegisterPkg {
+    name: String,
+    description: String,
+    addr_snippets: String,
+    struct_snippets: String,
+}
+
+impl SvRegisterPkg {
+    /// `base` is subtracted from the register offset (i.e. register of a relocatable block)
+    pub fn from_register(
+        section_name: &str,
+        word_w: &usize,
+        register: &Register,
+        base: usize,
+        tera: &Tera,
+    ) -> Self {
+        let mut context = tera::Context::new();
+        let base_name = format!("{section_name}_{}", register.name());
+        let mut ofs_name = format!("{base_name}_OFS");
+        ofs_name.make_ascii_uppercase();
+        context.insert("base_name", &base_name);
+        context.insert("ofs_name", &ofs_name);
+        context.insert("ofs_val", &format!("'h{:x}", register.offset() - base));
+
+        if let Some(fields) = register.field() {
+            // Sanitize fields -> insert padding if necessary
+            let mut cur_ofs = 0;
+            let mut padded_fields = Vec::new();
+            for f in fields {
+                if cur_ofs != *f.offset_b() {
+                    padded_fields.push((
+                        format!("padding_{cur_ofs}"),
+                        cur_ofs,
+                        (f.offset_b() - cur_ofs),
+                        None,
+                        None,
+                    ));
+                }
+                // NB: Struct keeps the maximum width to match the address layout, the width
+                // parameter and the reservation are only given as a hint.
+                // A `[P-1:0]` member isn't possible: the package doesn't see the module
+                // parameters and the padding up to the maximum width could be zero-width
+                padded_fields.push((
+                    f.name().clone(),
+                    *f.offset_b(),
+                    *f.size_b(),
+                    f.size_param().clone(),
+                    f.reserved().map(|p| p.to_string()),
+                ));
+                cur_ofs = f.offset_b() + f.size_b();
+            }
+            if cur_ofs != *word_w {
+                padded_fields.push((
+                    format!("padding_{cur_ofs}"),
+                    cur_ofs,
+                    (word_w - cur_ofs),
+                    None,
+                    None,
+                ));
+            }
+            // NB: SystemVerilog struct are defined from MSB word to LSB word
+            padded_fields.reverse();
+            context.insert("fields_nos", &padded_fields);
+        }
+
+        // Render addr section
+        let addr_snippets = tera.render("pkg/addr.sv", &context).unwrap();
+
+        // Render struct section
+        let struct_snippets = if register.field().is_some() {
+            tera.render("pkg/struct.sv", &context).unwrap()
+        } else {
+            String::new()
+        };
+
+        Self {
+            name: base_name,
+            description: register.description().clone(),
+            addr_snippets,
+            struct_snippets,
+        }
+    }
+}
+
+/// Reserved address range of a register module
+/// NB: Writes are always ignored, reads return 0 instead of the unmapped address pattern
+#[derive(Debug, Clone, Serialize)]
+struct RsvdRange {
+    name: String,
+    policy: String,
+    offset: usize,
+    last: usize,
+}
+
+/// Axi4-lite signal seen from the slave side
+#[derive(Debug, Clone, Serialize)]
+struct AxilSignal {
+    name: &'static str,
+    ty: &'static str,
+    /// Driven by the master
+    request: bool,
+}
+
+const fn axil(name: &'static str, ty: &'static str, request: bool) -> AxilSignal {
+    AxilSignal { name, ty, request }
+}
+
+/// Axi4-lite signals in port order
+const AXIL_SIGNALS: [AxilSignal; 16] = [
+    axil("awaddr", "logic [AXIL_ADD_W-1:0]", true),
+    axil("awvalid", "logic", true),
+    axil("awready", "logic", false),
+    axil("wdata", "logic [AXIL_DATA_W-1:0]", true),
+    axil("wvalid", "logic", true),
+    axil("wready", "logic", false),
+    axil("bresp", "logic [AXI4_RESP_W-1:0]", false),
+    axil("bvalid", "logic", false),
+    axil("bready", "logic", true),
+    axil("araddr", "logic [AXIL_ADD_W-1:0]", true),
+    axil("arvalid", "logic", true),
+    axil("arready", "logic", false),
+    axil("rdata", "logic [AXIL_DATA_W-1:0]", false),
+    axil("rresp", "logic [AXI4_RESP_W-1:0]", false),
+    axil("rvalid", "logic", false),
+    axil("rready", "logic", true),
+];
+
+/// User port of a register module (i.e. without clock, reset and Axi4-lite interface)
+#[derive(Debug, Clone, Serialize)]
+struct SvPort {
+    dir: &'static str,
+    ty: String,
+    name: String,
+}
+
+impl SvPort {
+    fn new(dir: &'static str, ty: &str, name: String) -> Self {
+        Self {
+            dir,
+            ty: ty.to_string(),
+            name,
+        }
+    }
+
+    /// Ports of the register module of a regmap
+    fn from_module(regmap: &Regmap) -> Vec<Self> {
+        Self::from_sections(regmap.module_name(), regmap.section().iter())
+    }
+
+    /// Ports of a register module built with the given sections
+    /// NB: Must be kept in phase with the module/io.sv template
+    fn from_sections<'a>(
+        module_name: &str,
+        sections: impl Iterator<Item = &'a Section>,
+    ) -> Vec<Self> {
+        let mut ports = vec![Self::new(
+            "output",
+            "logic [AXIL_DATA_W-1:0]",
+            "r_axil_wdata".to_string(),
+        )];
+        for sec in sections {
+            // NB: Reserved registers have no port
+            for reg in sec.register().iter().filter(|reg| reg.reserved().is_none()) {
+                let name = format!("{}_{}", sec.name(), reg.name());
+                let ty = match reg.field() {
+                    Some(_) => format!("{module_name}_pkg::{name}_t"),
+                    None => "logic [REG_DATA_W-1:0]".to_string(),
+                };
+                if !matches!(reg.owner(), Owner::Parameter) {
+                    ports.push(Self::new("output", &ty, format!("r_{name}")));
+                }
+                if matches!(reg.owner(), Owner::Kernel) {
+                    ports.push(Self::new("input ", &ty, format!("r_{name}_upd")));
+                }
+                if matches!(reg.read_access(), ReadAccess::ReadNotify) {
+                    ports.push(Self::new("output", "logic", format!("r_{name}_rd_en")));
+                }
+                if matches!(reg.write_access(), WriteAccess::WriteNotify) {
+                    ports.push(Self::new("output", "logic", format!("r_{name}_wr_en")));
+                }
+            }
+        }
+        ports
+    }
+
+    /// Ports of the top-level module of a regmap (i.e. with the ports of its child blocks)
+    fn from_top(regmap: &Regmap) -> Vec<Self> {
+        let mut ports = Self::from_module(regmap);
+        ports.extend(Self::from_blocks(regmap.block().iter()));
+        ports
+    }
+
+    /// Ports of the given child blocks, prefixed by the block name
+    fn from_blocks<'a>(blocks: impl Iterator<Item = &'a Block>) -> Vec<Self> {
+        blocks
+            .flat_map(|blk| {
+                Self::from_top(blk.regmap())
+                    .into_iter()
+                    .map(|port| port.prefixed(blk.name()))
+            })
+            .collect()
+    }
+
+    fn prefixed(self, prefix: &str) -> Self {
+        Self {
+            name: format!("{prefix}_{}", self.name),
+            ..self
+        }
+    }
+}
+
+/// Address window of a decoder target
+#[derive(Debug, Clone, Serialize)]
+struct DecTarget {
+    name: String,
+    /// Default target has no window
+    window: bool,
+    /// Target sees the address relative to its window
+    relative: bool,
+    offset: usize,
+    range: usize,
+}
+
+/// Module instantiated in a hierarchical top
+#[derive(Debug, Clone, Serialize)]
+struct SvInstance {
+    name: String,
+    module: String,
+    description: String,
+    /// Decoder target that drives the instance
+    target: String,
+    /// Prefix of the instance ports at top level
+    prefix: String,
+    ports: Vec<SvPort>,
+}
+
+/// Name of the module to instantiate for a regmap (i.e. with its child blocks)
+fn top_module_name(regmap: &Regmap) -> String {
+    if regmap.block().is_empty() {
+        regmap.module_name().clone()
+    } else {
+        format!("{}_top", regmap.module_name())
+    }
+}
+
+/// Module names of a decoded top (i.e. address decoder with its instances)
+struct DecNames {
+    top: String,
+    decoder: String,
+    /// Register map described by the top, used in the header
+    regmap: String,
+}
+
+/// Render an address decoder and the top module that instantiates its targets
+/// Requests outside of all the windows are routed to `default_target`, or answered with SLVERR
+fn render_decoded(
+    names: &DecNames,
+    ext_pkg: &HashSet<String>,
+    ports: &[SvPort],
+    targets: &[DecTarget],
+    instances: &[SvInstance],
+    default_target: Option<&str>,
+    ctx: &GenCtx,
+) -> Result<(String, String), anyhow::Error> {
+    // Targets with a window, msb first
+    let window_mask = targets
+        .iter()
+        .rev()
+        .map(|tgt| if tgt.window { '1' } else { '0' })
+        .collect::<String>();
+
+    let mut context = ctx.tera_context();
+    context.insert("module_name", &names.decoder);
+    context.insert("ext_pkg", ext_pkg);
+    context.insert("axil", &AXIL_SIGNALS);
+    context.insert("targets", targets);
+    context.insert("default_target", &default_target);
+    context.insert("window_mask", &format!("'b{window_mask}"));
+    let decoder = post_process(&ctx.tera.render("decoder.sv", &context)?);
+
+    let mut context = ctx.tera_context();
+    context.insert("module_name", &names.top);
+    context.insert("regmap_name", &names.regmap);
+    context.insert("decoder_name", &names.decoder);
+    context.insert("ext_pkg", ext_pkg);
+    context.insert("axil", &AXIL_SIGNALS);
+    context.insert("ports", ports);
+    context.insert("targets", targets);
+    context.insert("default_target", &default_target);
+    context.insert("instances", instances);
+    let top = post_process(&ctx.tera.render("top.sv", &context)?);
+
+    Ok((decoder, top))
+}
+
+/// Generate the RTL module of a regmap slice
+pub struct SvModuleGen;
+
+impl SvModuleGen {
+    /// Render the register module of a regmap (i.e. only its own sections)
+    /// `base` is the address of the regmap seen by the module (i.e. 0 for a block)
+    fn render_module(regmap: &Regmap, base: usize, ctx: &GenCtx) -> Result<String, anyhow::Error> {
+        // Convert regmap in rtl snippets based on Tera
+        let mut regs_sv = Vec::new();
+        let mut used_params = Vec::new();
+        regmap.section().iter().for_each(|sec| {
+            sec.register().iter().for_each(|reg| {
+                regs_sv.push(SvRegister::from_register(
+                    sec.name(),
+                    regmap.word_size_b(),
+                    reg,
+                    &mut used_params,
+                    regmap.param(),
+                    ctx.tera,
+                ));
+            })
+        });
+
+        let rsvd_ranges = regmap
+            .section()
+            .iter()
+            .filter_map(|sec| {
+                sec.reserved().map(|policy| RsvdRange {
+                    name: sec.name().clone(),
+                    policy: policy.to_string(),
+                    offset: sec.offset() - base,
+                    last: sec.offset() + sec.range() - 1 - base,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Expand to rtl module
+        let mut context = ctx.tera_context();
+        context.insert("module_name", &regmap.module_name());
+        context.insert("word_size_b", &regmap.word_size_b());
+        context.insert("offset", &(regmap.offset() - base));
+        context.insert("ext_pkg", &regmap.ext_pkg());
+        context.insert("range", &regmap.range());
+        context.insert("regs_sv", &regs_sv);
+        context.insert("rsvd_ranges", &rsvd_ranges);
+        Ok(post_process(&ctx.tera.render("module.sv", &context)?))
+    }
+
+    /// Render the address decoder and the top module of a hierarchical regmap
+    /// NB: Decoder forwards the addresses relative to the block window, blocks are relocatable
+    fn render_top(
+        regmap: &Regmap,
+        base: usize,
+        ctx: &GenCtx,
+    ) -> Result<(String, String), anyhow::Error> {
+        let decoder_name = format!("{}_dec", regmap.module_name());
+        // NB: Local registers are the default target, blocks have their own window
+        let mut targets = vec![DecTarget {
+            name: "regs".to_string(),
+            window: false,
+            relative: false,
+            offset: regmap.offset() - base,
+            range: *regmap.range(),
+        }];
+        let mut instances = vec![SvInstance {
+            name: "regs".to_string(),
+            module: regmap.module_name().clone(),
+            description: "Local registers".to_string(),
+            target: "regs".to_string(),
+            prefix: String::new(),
+            ports: SvPort::from_module(regmap),
+        }];
+        for blk in regmap.block().iter() {
+            targets.push(DecTarget {
+                name: blk.name().clone(),
+                window: true,
+                relative: true,
+                offset: blk.offset() - base,
+                range: *blk.range(),
+            });
+            instances.push(SvInstance {
+                name: blk.name().clone(),
+                module: top_module_name(blk.regmap()),
+                description: format!("Block {}: {}", blk.name(), blk.description()),
+                target: blk.name().clone(),
+                prefix: format!("{}_", blk.name()),
+                ports: SvPort::from_top(blk.regmap()),
+            });
+        }
+        let names = DecNames {
+            top: top_module_name(regmap),
+            decoder: decoder_name,
+            regmap: regmap.module_name().clone(),
+        };
+        render_decoded(
+            &names,
+            regmap.ext_pkg(),
+            &SvPort::from_top(regmap),
+            &targets,
+            &instances,
+            Some("regs"),
+            ctx,
+        )
+    }
+
+    /// Render the modules of a regmap and recursively of its blocks
+    /// NB: Blocks are rendered at offset 0, thus instances of the same block share their modules
+    fn render_all(
+        regmap: &Regmap,
+        base: usize,
+        ctx: &GenCtx,
+        files: &mut Vec<GenFile>,
+    ) -> Result<(), anyhow::Error> {
+        let path = format!("{}.sv", regmap.module_name());
+        if files.iter().any(|f| f.path == path) {
+            return Ok(());
+        }
+        files.push(GenFile {
+            path,
+            content: Self::render_module(regmap, base, ctx)?,
+        });
+        if !regmap.block().is_empty() {
+            let (decoder, top) = Self::render_top(regmap, base, ctx)?;
+            files.push(GenFile {
+                path: format!("{}_dec.sv", regmap.module_name()),
+                content: decoder,
+            });
+            files.push(GenFile {
+                path: format!("{}.sv", top_module_name(regmap)),
+                content: top,
+            });
+        }
+        for blk in regmap.block().iter() {
+            Self::render_all(blk.regmap(), *blk.offset(), ctx, files)?;
+        }
+        Ok(())
+    }
+}
+
+/// Output files of a regmap and recursively of its blocks
+fn hier_outputs(
+    regmap: &Regmap,
+    files: &mut Vec<String>,
+    outputs: &dyn Fn(&Regmap) -> Vec<String>,
+) {
+    for file in outputs(regmap) {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+    for blk in regmap.block().iter() {
+        hier_outputs(blk.regmap(), files, outputs);
+    }
+}
+
+impl Generator for SvModuleGen {
+    fn name(&self) -> &str {
+        "sv"
+    }
+
+    fn description(&self) -> &str {
+        "SystemVerilog register module"
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Slice
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        let mut files = Vec::new();
+        hier_outputs(regmap, &mut files, &|regmap| {
+            let mut files = vec![format!("{}.sv", regmap.module_name())];
+            if !regmap.block().is_empty() {
+                files.push(format!("{}_dec.sv", regmap.module_name()));
+                files.push(format!("{}.sv", top_module_name(regmap)));
+            }
+            files
+        });
+        files
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        let mut files = Vec::new();
+        Self::render_all(regmap, 0, ctx, &mut files)?;
+        Ok(files)
+    }
+}
+
+/// Generate the addr/field package of a regmap slice
+/// Package of a hierarchical regmap also contains the absolute offsets of the blocks registers,
+/// blocks packages use offsets relative to the block base.
+pub struct SvPkgGen;
+
+impl SvPkgGen {
+    /// Render the package of a regmap
+    /// `base` is the address of the regmap seen by the package (i.e. 0 for a block)
+    fn render_pkg(regmap: &Regmap, base: usize, ctx: &GenCtx) -> Result<String, anyhow::Error> {
+        // Convert regmap in pkg snippets based on Tera
+        // NB: Structures of the blocks registers are defined in their own package
+        let mut regs_pkg_sv = Vec::new();
+        for (sec_name, sec) in regmap.all_sections() {
+            let local = !sec_name.contains("::");
+            sec.register().iter().for_each(|reg| {
+                let mut reg_pkg = SvRegisterPkg::from_register(
+                    &sec_name.replace("::", "_"),
+                    regmap.word_size_b(),
+                    reg,
+                    base,
+                    ctx.tera,
+                );
+                if !local {
+                    reg_pkg.struct_snippets.clear();
+                }
+                regs_pkg_sv.push(reg_pkg);
+            });
+        }
+
+        // Expand to rtl package
+        let mut context = ctx.tera_context();
+        context.insert("module_name", &regmap.module_name());
+        context.insert("word_size_b", &regmap.word_size_b());
+        context.insert("regs_pkg_sv", &regs_pkg_sv);
+        Ok(post_process(&ctx.tera.render("pkg.sv", &context)?))
+    }
+
+    /// Render the packages of a regmap and recursively of its blocks
+    fn render_all(
+        regmap: &Regmap,
+        base: usize,
+        ctx: &GenCtx,
+        files: &mut Vec<GenFile>,
+    ) -> Result<(), anyhow::Error> {
+        let path = format!("{}_pkg.sv", regmap.module_name());
+        if files.iter().any(|f| f.path == path) {
+            return Ok(());
+        }
+        files.push(GenFile {
+            path,
+            content: Self::render_pkg(regmap, base, ctx)?,
+        });
+        for blk in regmap.block().iter() {
+            Self::render_all(blk.regmap(), *blk.offset(), ctx, files)?;
+        }
+        Ok(())
+    }
+}
+
+impl Generator for SvPkgGen {
+    fn name(&self) -> &str {
+        "pkg"
+    }
+
+    fn description(&self) -> &str {
+        "SystemVerilog package with register addresses and field structures"
+    }
+
+    fn scope(&self) -> Scope {
+        Scope::Slice
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        let mut files = Vec::new();
+        hier_outputs(regmap, &mut files, &|regmap| {
+            vec![format!("{}_pkg.sv", regmap.module_name())]
+        });
+        files
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        let mut files = Vec::new();
+        Self::render_all(regmap, 0, ctx, &mut files)?;
+        Ok(files)
+    }
+}
+
+/// Generate the interconnect of a multi-slice build
+/// Each slice module is instantiated behind an address decoder, holes between the slices are
+/// answered with SLVERR. Slices see the full address since their modules check their own offset.
+pub struct SvInterconnectGen;
+
+impl SvInterconnectGen {
+    fn names(regmap: &Regmap) -> DecNames {
+        DecNames {
+            top: format!("{}_interconnect", regmap.module_name()),
+            decoder: format!("{}_interconnect_dec", regmap.module_name()),
+            regmap: regmap.module_name().clone(),
+        }
+    }
+}
+
+impl Generator for SvInterconnectGen {
+    fn name(&self) -> &str {
+        "interconnect"
+    }
+
+    fn description(&self) -> &str {
+        "SystemVerilog interconnect that routes the requests toward the slice modules"
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        // NB: Nothing to route with a single slice
+        if regmap.slice().is_empty() {
+            return Vec::new();
+        }
+        let names = Self::names(regmap);
+        vec![format!("{}.sv", names.top), format!("{}.sv", names.decoder)]
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        if regmap.slice().is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut targets = Vec::with_capacity(regmap.slice().len());
+        let mut instances = Vec::with_capacity(regmap.slice().len());
+        let mut ports = Vec::new();
+        for slice in regmap.slice().iter() {
+            let name = slice.module_name();
+            let section = regmap
+                .section()
+                .iter()
+                .filter(|sec| slice.section().contains(sec.name()));
+            let block = regmap
+                .block()
+                .iter()
+                .filter(|blk| slice.block().contains(blk.name()));
+            let mut slice_ports = SvPort::from_sections(name, section);
+            slice_ports.extend(SvPort::from_blocks(block.clone()));
+
+            targets.push(DecTarget {
+                name: name.clone(),
+                window: true,
+                relative: false,
+                offset: *slice.offset(),
+                range: *slice.range(),
+            });
+            instances.push(SvInstance {
+                name: name.clone(),
+                module: if block.count() == 0 {
+                    name.clone()
+                } else {
+                    format!("{name}_top")
+                },
+                description: format!("Slice {name}: {}", slice.description()),
+                target: name.clone(),
+                prefix: format!("{name}_"),
+                ports: slice_ports.clone(),
+            });
+            // NB: Slices share the register names space but not the module ports (e.g. r_axil_wdata)
+            ports.extend(slice_ports.into_iter().map(|port| port.prefixed(name)));
+        }
+
+        let names = Self::names(regmap);
+        let (decoder, top) = render_decoded(
+            &names,
+            regmap.ext_pkg(),
+            &ports,
+            &targets,
+            &instances,
+            None,
+            ctx,
+        )?;
+        Ok(vec![
+            GenFile {
+                path: format!("{}.sv", names.top),
+                content: top,
+            },
+            GenFile {
+                path: format!("{}.sv", names.decoder),
+                content: decoder,
+            },
+        ])
+    }
+}
@@ -0,0 +1,177 @@
+//!
+//! Software generators
+//! Regmap is converted in constants (i.e. register offsets, field shifts and masks) usable by Sw drivers
+//!
+
+use serde::Serialize;
+
+use super::{GenCtx, GenFile, Generator};
+use crate::model::field_mask;
+use crate::regmap::Regmap;
+use crate::regmap::parser::Reserved;
+
+#[derive(Debug, Clone, Serialize)]
+struct SwField {
+    name: String,
+    description: String,
+    shift: usize,
+    size_b: usize,
+    mask: u64,
+    /// Reserved bits are only documented, the software mustn't use them
+    reserved: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SwRegister {
+    name: String,
+    description: String,
+    offset: usize,
+    /// Reset value, None if it relies on a parameter without value
+    reset: Option<usize>,
+    reserved: Option<String>,
+    /// Reserved bits of the fields, and the ones that must be written back as read
+    rsvd_mask: u64,
+    preserve_mask: u64,
+    field: Vec<SwField>,
+}
+
+/// Reserved address range
+#[derive(Debug, Clone, Serialize)]
+struct SwRange {
+    name: String,
+    description: String,
+    offset: usize,
+    range: usize,
+    reserved: String,
+}
+
+impl SwRange {
+    /// Reserved sections of the regmap, with upper-case constant names
+    fn from_regmap(regmap: &Regmap) -> Vec<Self> {
+        regmap
+            .all_sections()
+            .into_iter()
+            .filter_map(|(sec_name, sec)| {
+                sec.reserved().map(|policy| Self {
+                    name: sec_name.replace("::", "_").to_ascii_uppercase(),
+                    description: sec.description().clone(),
+                    offset: *sec.offset(),
+                    range: *sec.range(),
+                    reserved: policy.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl SwRegister {
+    /// Flatten regmap in a list of registers with upper-case constant names
+    /// NB: Registers of child blocks are prefixed by the block instance path
+    fn from_regmap(regmap: &Regmap) -> Vec<Self> {
+        regmap
+            .all_sections()
+            .into_iter()
+            .flat_map(|(sec_name, sec)| {
+                sec.register().iter().map(move |reg| {
+                    let name = format!("{}_{}", sec_name.replace("::", "_"), reg.name())
+                        .to_ascii_uppercase();
+                    let field = reg
+                        .field()
+                        .iter()
+                        .flatten()
+                        .map(|f| SwField {
+                            name: format!("{name}_{}", f.name().to_ascii_uppercase()),
+                            description: f.description().clone(),
+                            shift: *f.offset_b(),
+                            size_b: f.width(),
+                            mask: field_mask(f.width()) << f.offset_b(),
+                            reserved: f.reserved().map(|p| p.to_string()),
+                        })
+                        .collect();
+                    let (mut rsvd_mask, mut preserve_mask) = (0, 0);
+                    for f in reg.field().iter().flatten() {
+                        let mask = field_mask(*f.size_b()) << f.offset_b();
+                        match f.reserved() {
+                            Some(Reserved::PreserveOnWrite) => {
+                                rsvd_mask |= mask;
+                                preserve_mask |= mask;
+                            }
+                            Some(_) => rsvd_mask |= mask,
+                            None => {}
+                        }
+                    }
+                    Self {
+                        name,
+                        description: reg.description().clone(),
+                        offset: *reg.offset(),
+                        reset: *reg.reset(),
+                        reserved: reg.reserved().map(|p| p.to_string()),
+                        rsvd_mask,
+                        preserve_mask,
+                        field,
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Render a Sw template with the flatten view of the regmap
+fn render_sw(template: &str, regmap: &Regmap, ctx: &GenCtx) -> Result<String, anyhow::Error> {
+    let mut context = ctx.tera_context();
+    context.insert("module_name", &regmap.module_name());
+    context.insert("word_size_b", &regmap.word_size_b());
+    context.insert("offset", &regmap.offset());
+    context.insert("range", &regmap.range());
+    context.insert("regs", &SwRegister::from_regmap(regmap));
+    context.insert("rsvd_ranges", &SwRange::from_regmap(regmap));
+    Ok(ctx.tera.render(template, &context)?)
+}
+
+/// Generate a C header with register offsets and field definitions
+pub struct CHeaderGen;
+
+impl Generator for CHeaderGen {
+    fn name(&self) -> &str {
+        "c"
+    }
+
+    fn description(&self) -> &str {
+        "C header with register offsets and field shifts/masks"
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        vec![format!("{}.h", regmap.module_name())]
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        Ok(vec![GenFile {
+            path: self.outputs(regmap).remove(0),
+            content: render_sw("sw/regmap.h", regmap, ctx)?,
+        }])
+    }
+}
+
+/// Generate a Rust module with register offsets and field definitions
+pub struct RustGen;
+
+impl Generator for RustGen {
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    fn description(&self) -> &str {
+        "Rust module with register offsets and field shifts/masks"
+    }
+
+    fn outputs(&self, regmap: &Regmap) -> Vec<String> {
+        vec![format!("{}.rs", regmap.module_name())]
+    }
+
+    fn generate(&self, regmap: &Regmap, ctx: &GenCtx) -> Result<Vec<GenFile>, anyhow::Error> {
+        Ok(vec![GenFile {
+            path: self.outputs(regmap).remove(0),
+            content: render_sw("sw/regmap.rs", regmap, ctx)?,
+        }])
+    }
+}
@@ -0,0 +1,122 @@
+//!
+//! Built-in templates
+//! Shipped templates are embedded in the binary, thus the tool doesn't depend on the current directory.
+//! User templates could be overlaid on top of them to replace only a subset of the snippets.
+//!
+
+use std::path::{Path, PathBuf};
+
+use tera::Tera;
+
+/// Embed a template with its name relative to the templates folder
+macro_rules! builtin {
+    ($name: literal) => {
+        ($name, include_str!(concat!("../../templates/", $name)))
+    };
+}
+
+/// List of built-in templates (name, content)
+pub const BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    builtin!("module.sv"),
+    builtin!("module/check.sv"),
+    builtin!("module/default.sv"),
+    builtin!("module/io.sv"),
+    builtin!("module/param.sv"),
+    builtin!("module/read.sv"),
+    builtin!("module/write.sv"),
+    builtin!("pkg.sv"),
+    builtin!("pkg/addr.sv"),
+    builtin!("pkg/struct.sv"),
+    builtin!("decoder.sv"),
+    builtin!("top.sv"),
+    builtin!("docs/fmt_as.json"),
+    builtin!("docs/fmt_as.md"),
+    builtin!("sw/regmap.h"),
+    builtin!("sw/regmap.rs"),
+];
+
+/// Create a templating engine with the built-in templates
+/// Templates found in `template_dir` are overlaid on top of them (i.e. same relative name replaces
+/// the built-in one, other names are added)
+/// All helper functions used by the built-in templates are registered
+pub fn tera_engine(template_dir: Option<&Path>) -> Result<Tera, anyhow::Error> {
+    let mut tera = Tera::default();
+    tera.add_raw_templates(BUILTIN_TEMPLATES.iter().copied())?;
+
+    if let Some(dir) = template_dir {
+        let mut files = Vec::new();
+        list_files(dir, &mut files).map_err(|err| {
+            anyhow::anyhow!("Unable to read template dir {}: {err}", dir.display())
+        })?;
+        let user_templates = files
+            .iter()
+            .map(|path| {
+                // NB: Template name use `/` separator whatever the platform
+                let name = path
+                    .strip_prefix(dir)
+                    .expect("Listed files are in template dir")
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let content = std::fs::read_to_string(path).map_err(|err| {
+                    anyhow::anyhow!("Unable to read template {}: {err}", path.display())
+                })?;
+                Ok((name, content))
+            })
+            .collect::<Result<Vec<_>, anyhow::Error>>()?;
+        tera.add_raw_templates(user_templates)?;
+    }
+
+    tera.register_function("as_hex", super::as_hex);
+    tera.register_function("as_sv_hex", super::as_sv_hex);
+    Ok(tera)
+}
+
+/// Write the built-in templates in output_path for customization
+/// Existing files are only overwritten with `force`
+/// NB: All targets are checked before writing, thus output_path is left untouched on conflict
+/// Return the list of written files
+pub fn dump(output_path: &Path, force: bool) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let targets = BUILTIN_TEMPLATES
+        .iter()
+        .map(|(name, content)| (output_path.join(name), content))
+        .collect::<Vec<_>>();
+    if !force {
+        let existing = targets
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, _)| path.display().to_string())
+            .collect::<Vec<_>>();
+        if !existing.is_empty() {
+            anyhow::bail!(
+                "Templates already exist [use --force to overwrite them]: {}",
+                existing.join(", ")
+            );
+        }
+    }
+
+    let mut written = Vec::with_capacity(targets.len());
+    for (path, content) in targets {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)
+            .map_err(|err| anyhow::anyhow!("Unable to write file {}: {err}", path.display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Recursively list the files of a directory
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -1,8 +1,27 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod flat;
+
+// NB: Model, parsing and elaboration live in `hw_regmap_core`, shared with `hw_regmap_macros`
+pub use hw_regmap_core::{error, model, span};
+
+#[cfg(feature = "generator")]
+pub mod diff;
+#[cfg(feature = "generator")]
 pub mod generator;
-pub mod regmap;
+#[cfg(feature = "generator")]
+pub use hw_regmap_core::regmap;
 
+pub use error::RegmapError;
 pub use flat::FlatRegmap;
 
+#[cfg(feature = "generator")]
 pub use regmap::parser::RegmapOpt;
+#[cfg(feature = "generator")]
 pub use regmap::Regmap;
+
+/// Compile-time register map embedding, cf. `hw_regmap_macros`
+#[cfg(feature = "macros")]
+pub use hw_regmap_macros::include_regmap;
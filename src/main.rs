@@ -1,245 +1,589 @@
-use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+use hw_regmap::FlatRegmap;
+use hw_regmap::diff::RegmapDiff;
 use hw_regmap::generator;
+use hw_regmap::generator::{GenCtx, GeneratorRegistry, templates};
 use hw_regmap::regmap;
-
-use regex::Regex;
-use tera::Tera;
+use hw_regmap::regmap::alloc::Strategy;
+use hw_regmap::regmap::diag::Diagnostics;
+use hw_regmap::regmap::lock::RegmapLock;
+use hw_regmap::regmap::param::ParamSet;
+use hw_regmap::regmap::{ElabOpt, RegmapError};
 
 /// Define CLI arguments
 use clap::Parser;
 #[derive(clap::Parser, Debug, Clone)]
 #[clap(long_about = "Generate RTL register map")]
 pub struct Args {
-    // Regmap configuration ----------------------------------------------------
-    #[clap(long, value_parser)]
-    toml_file: Vec<String>,
+    #[command(subcommand)]
+    cmd: Command,
 
     // Output configuration ----------------------------------------------------
-    // Output folder path
+    /// Format used to report results and errors
+    #[clap(long, value_enum, global = true, default_value_t = Format::Human)]
+    format: Format,
+
+    // Debug options ----------------------------------------------------------
+    /// Enable verbosity
+    #[clap(long, value_parser, global = true)]
+    verbose: bool,
+}
+
+#[derive(clap::Subcommand, Debug, Clone)]
+enum Command {
+    /// Check the register map and generate the selected outputs (RTL, documentation, Sw headers)
+    Generate(GenerateArgs),
+    /// Only check the register map, nothing is generated
+    Check(RegmapArgs),
+    /// Print address and fields of registers
+    Query(QueryArgs),
+    /// Rewrite the toml files in the canonical style
+    Fmt(FmtArgs),
+    /// Compare two register maps and flag ABI-breaking changes
+    Diff(DiffArgs),
+    /// Dump the built-in templates for customization (cf. generate --template-dir)
+    Templates(TemplatesArgs),
+    /// Print the JSON Schema of the toml definitions (i.e. editor validation and completion)
+    Schema(SchemaArgs),
+}
+
+/// Register map shared by all the subcommands
+#[derive(clap::Args, Debug, Clone)]
+struct RegmapArgs {
+    /// Toml register map definition. Multiple files are fused in one register map
+    #[clap(long, value_parser, required = true)]
+    toml_file: Vec<String>,
+
+    #[command(flatten)]
+    elab: ElabArgs,
+
+    /// Fail if the lock file is missing or out of date instead of updating it
+    #[clap(long, value_parser, requires = "lock_file")]
+    locked: bool,
+}
+
+/// Elaboration options shared by all the subcommands
+#[derive(clap::Args, Debug, Clone)]
+struct ElabArgs {
+    /// Lock file with the frozen addresses. Locked addresses are kept and new registers are
+    /// placed in free holes. Created or updated by `generate`
+    #[clap(long, value_parser)]
+    lock_file: Option<String>,
+
+    /// Allocation strategy of the registers and sections without explicit offset
+    /// [available: sequential, first_fit, best_fit]
+    #[clap(long, value_parser, default_value_t = Strategy::Sequential)]
+    alloc_strategy: Strategy,
+
+    /// Toml or json file with the values of the RTL parameters used as default value or field
+    /// width (i.e. flat `NAME = VAL` entries)
+    #[clap(long, value_parser)]
+    param_file: Option<String>,
+
+    /// Value of a RTL parameter used as default value or field width with `NAME=VAL` syntax. Could
+    /// be repeated, overrides the values of the param file
+    #[clap(long = "param", value_parser = ParamSet::parse_assign)]
+    param: Vec<(String, usize)>,
+
+    /// Comma-separated list of enabled features. Elements with an `if` predicate are kept only
+    /// when it holds
+    #[clap(long = "feature", value_parser, value_delimiter = ',')]
+    feature: Vec<String>,
+
+    /// Build variant, i.e. named set of features of the `[variant]` table
+    #[clap(long, value_parser)]
+    variant: Option<String>,
+
+    /// Keep the excluded elements as read-as-zero reserved ones, thus the layout is the same for
+    /// all the variants
+    #[clap(long, value_parser)]
+    reserve_excluded: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct GenerateArgs {
+    #[command(flatten)]
+    regmap: RegmapArgs,
+
+    /// Output folder path
     #[clap(long, value_parser, default_value = "output")]
     output_path: String,
 
-    // Basename of the generated file
+    /// Basename of the generated file
     #[clap(long, value_parser, default_value = "regmap")]
     basename: String,
 
-    // Debug options ----------------------------------------------------------
-    /// Enable verbosity
+    /// Comma-separated list of generators to run [available: sv, pkg, interconnect, md, json, toml, c, rust]
+    #[clap(
+        long,
+        value_parser,
+        value_delimiter = ',',
+        default_value = "sv,pkg,interconnect,md,json"
+    )]
+    emit: Vec<String>,
+
+    /// Folder with user templates overlaid on top of the built-in ones.
+    /// Only the templates to customize are required (e.g. `module/write.sv`)
     #[clap(long, value_parser)]
-    verbose: bool,
+    template_dir: Option<String>,
 }
 
-/// Simple post-process of String generated by templating engine
-/// Templating introduce a lot of consecutive newlines due to filtering
-/// -> This function remove consecutive newline to have something more readable in the end
-fn post_process(raw: &str) -> String {
-    // Remove extra new-line generated by tera templating
-    let regex = Regex::new(r"\s*\n\s*\n+").unwrap();
-    let post_rendered = regex.replace_all(raw, "\n");
+#[derive(clap::Args, Debug, Clone)]
+struct FmtArgs {
+    /// Toml register map definition to format
+    #[clap(long, value_parser, required = true)]
+    toml_file: Vec<String>,
 
-    post_rendered.to_string()
+    /// Only check that files are already formatted, nothing is rewritten
+    #[clap(long, value_parser)]
+    check: bool,
 }
 
-/// Simple function used to render integer in hexadecimal format with tera
-/// Syntax in tera file is: as_hex()
-pub fn as_hex(args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
-    // Extract width if specified
-    let width = if let Some(width) = args.get("width") {
-        if let tera::Value::Number(num) = width {
-            Ok(num.as_u64().unwrap() as usize)
-        } else {
-            Err(tera::Error::msg("Width is not an integer"))
-        }
-    } else {
-        Ok(0)
-    }?;
-
-    if let Some(value) = args.get("val") {
-        if let tera::Value::Number(num) = value {
-            let hex_str = format!("0x{:0>width$x}", num.as_u64().unwrap(), width = width);
-            Ok(tera::Value::String(hex_str))
-        } else {
-            Err(tera::Error::msg("Value is not an integer"))
-        }
-    } else {
-        Err(tera::Error::msg(
-            "Function `as_hex` didn't receive a `val` argument",
-        ))
+#[derive(clap::Args, Debug, Clone)]
+struct DiffArgs {
+    /// Toml definition of the reference register map. Multiple files are fused in one register map
+    #[clap(long, value_parser, required = true)]
+    old: Vec<String>,
+
+    /// Toml definition of the new register map. Multiple files are fused in one register map
+    #[clap(long, value_parser, required = true)]
+    new: Vec<String>,
+
+    /// Both register maps are elaborated with the same options (i.e. same variant)
+    #[command(flatten)]
+    elab: ElabArgs,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct TemplatesArgs {
+    /// Output folder of the built-in templates
+    #[clap(long, value_parser, default_value = "templates")]
+    output_path: String,
+
+    /// Overwrite existing files
+    #[clap(long, value_parser)]
+    force: bool,
+
+    /// Only list the built-in templates
+    #[clap(long, value_parser)]
+    list: bool,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct SchemaArgs {
+    /// Schema of the included files instead of the regmap definition
+    #[clap(long, value_parser)]
+    fragment: bool,
+
+    /// Output file, the schema is printed on stdout otherwise
+    #[clap(long, value_parser)]
+    output_path: Option<String>,
+}
+
+#[derive(clap::Args, Debug, Clone)]
+struct QueryArgs {
+    #[command(flatten)]
+    regmap: RegmapArgs,
+
+    /// Register to query with `section::register` syntax (`block::section::register` in blocks).
+    /// A section or block name alone matches all its registers
+    #[clap(value_parser)]
+    name: String,
+}
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Human readable output. Errors are reported compiler-style on stderr
+    Human,
+    /// Json output on stdout. Errors are reported as a list of structured errors
+    Json,
+}
+
+/// Process exit codes
+mod exit_code {
+    /// Register map is valid and the requested action succeeded
+    pub const SUCCESS: i32 = 0;
+    /// Invalid command line (i.e. same code as clap parsing errors)
+    pub const USAGE: i32 = 2;
+    /// Register map definition is invalid
+    pub const INVALID_REGMAP: i32 = 1;
+    /// Queried element isn't available in the register map
+    pub const NOT_FOUND: i32 = 3;
+    /// Outputs couldn't be generated (i.e. I/O or templating errors)
+    pub const GENERATION: i32 = 4;
+    /// Compared register maps have ABI-breaking changes
+    pub const BREAKING_CHANGE: i32 = 5;
+    /// Toml files aren't in the canonical style (i.e. fmt --check)
+    pub const UNFORMATTED: i32 = 6;
+}
+
+/// Report all the gathered errors in the requested format and exit
+fn report_and_exit(diag: &Diagnostics, format: Format) -> ! {
+    match format {
+        Format::Human => eprintln!("{diag}"),
+        Format::Json => println!("{}", diag.to_json()),
     }
+    std::process::exit(exit_code::INVALID_REGMAP);
 }
 
-/// Simple function used to render integer in hexadecimal SystemVerilog format with tera
-/// Syntax in tera file is: as_sv_hex()
-pub fn as_sv_hex(args: &HashMap<String, tera::Value>) -> tera::Result<tera::Value> {
-    // Extract width if specified
-    let width = if let Some(width) = args.get("width") {
-        if let tera::Value::Number(num) = width {
-            Ok(Some(num.as_u64().unwrap() as usize))
-        } else {
-            Err(tera::Error::msg("Width is not an integer"))
-        }
-    } else {
-        Ok(None)
-    }?;
-
-    if let Some(value) = args.get("val") {
-        if let tera::Value::Number(num) = value {
-            let hex_str = format!(
-                "{}'h{:x}",
-                if let Some(w) = width {
-                    format!("{w}")
-                } else {
-                    "".to_string()
-                },
-                num.as_u64().unwrap(),
-            );
-            Ok(tera::Value::String(hex_str))
-        } else {
-            Err(tera::Error::msg("Value is not an integer"))
+/// Parse and check the register map of the command line
+/// Exit with all the gathered errors on failure
+fn load_regmap(toml_files: &[String], elab: &ElabOpt, format: Format) -> regmap::Regmap {
+    let toml_files = toml_files.iter().map(|t| t.as_str()).collect::<Vec<_>>();
+    regmap::Regmap::check_files_with(&toml_files, elab)
+        .unwrap_or_else(|diag| report_and_exit(&diag, format))
+}
+
+/// Elaboration options of the command line
+/// Lock file is only used if it exists (i.e. created on first generation)
+fn elab_opt(args: &ElabArgs, format: Format) -> ElabOpt {
+    let lock = match args.lock_file.as_ref() {
+        Some(file) if Path::new(file).exists() => Some(
+            RegmapLock::read_from(file).unwrap_or_else(|err| report_and_exit(&err.into(), format)),
+        ),
+        _ => None,
+    };
+    let mut params = match args.param_file.as_ref() {
+        Some(file) => {
+            ParamSet::read_from(file).unwrap_or_else(|err| report_and_exit(&err.into(), format))
         }
-    } else {
-        Err(tera::Error::msg(
-            "Function `as_sv_hex` didn't receive a `val` argument",
-        ))
-    }
-}
-
-fn generate_sv(regmap: &regmap::Regmap, output_path: &str, engine: &Tera) {
-    // Generate module body  ==================================================
-    let rtl_module = format!("{}/{}.sv", output_path, regmap.module_name());
-
-    // Convert regmap in rtl snippets based on Tera
-    let mut regs_sv = Vec::new();
-    let mut used_params = Vec::new();
-    regmap.section().iter().for_each(|sec| {
-        sec.register().iter().for_each(|reg| {
-            regs_sv.push(generator::SvRegister::from_register(
-                sec.name(),
-                reg,
-                &mut used_params,
-                engine,
-            ));
-        })
-    });
-
-    // Expand to rtl module and store in targeted file
-    let mut context = tera::Context::new();
-    // Extract version from env
-    let git_version = option_env!("GIT_VERSION").unwrap_or("unknown");
-    context.insert("tool_version", git_version);
-    context.insert("module_name", &regmap.module_name());
-    context.insert("word_size_b", &regmap.word_size_b());
-    context.insert("offset", &regmap.offset());
-    context.insert("ext_pkg", &regmap.ext_pkg());
-    context.insert("range", &regmap.range());
-    context.insert("regs_sv", &regs_sv);
-    let module_rendered = engine.render("module.sv", &context).unwrap();
-    let module_post_rendered = post_process(&module_rendered);
-    std::fs::write(&rtl_module, module_post_rendered)
-        .unwrap_or_else(|_| panic!("Unable to write file {rtl_module}"));
-
-    // Generate addr/field pkg ================================================
-    let rtl_pkg = format!("{}/{}_pkg.sv", output_path, regmap.module_name());
-
-    // Convert regmap in pkg snippets based on Tera
-    let mut regs_pkg_sv = Vec::new();
-    regmap.section().iter().for_each(|sec| {
-        sec.register().iter().for_each(|reg| {
-            regs_pkg_sv.push(generator::SvRegisterPkg::from_register(
-                sec.name(),
-                regmap.word_size_b(),
-                reg,
-                engine,
-            ));
-        })
-    });
-
-    // Expand to rtl module and store in targeted file
-    let mut context = tera::Context::new();
-    // Extract version from env
-    let git_version = option_env!("GIT_VERSION").unwrap_or("unknown");
-    context.insert("tool_version", git_version);
-    context.insert("module_name", &regmap.module_name());
-    context.insert("word_size_b", &regmap.word_size_b());
-    context.insert("regs_pkg_sv", &regs_pkg_sv);
-    let pkg_rendered = engine.render("pkg.sv", &context).unwrap();
-    let pkg_post_rendered = post_process(&pkg_rendered);
-
-    std::fs::write(&rtl_pkg, pkg_post_rendered)
-        .unwrap_or_else(|_| panic!("Unable to write file {rtl_pkg}"));
-}
-
-/// Generate Markdown and Json documentation in output_path folder
-fn generate_doc(regmap: &regmap::Regmap, output_path: &str, engine: &Tera) -> std::io::Result<()> {
-    // Generate documentation ====================================================================
-    // JSON
-    // Serialize as json for full access all fields
-    let doc_json = format!("{}/{}_doc.json", output_path, regmap.module_name());
-    let doc_json_f = std::fs::File::create(&doc_json)?;
-    serde_json::to_writer_pretty(doc_json_f, &regmap)?;
-
-    // Markdown
-    // Generate a structure document targeting online documentation
-    let doc_md = format!("{}/{}_doc.md", output_path, regmap.module_name());
-    // Expand to docs and store in targeted file
-    let mut context = tera::Context::new();
-    // Extract version from env
-    let git_version = option_env!("GIT_VERSION").unwrap_or("unknown");
-    context.insert("tool_version", git_version);
-    context.insert("regmap", &regmap);
-    let md_rendered = engine.render("docs/fmt_as.md", &context).unwrap();
-    std::fs::write(&doc_md, md_rendered)
-        .unwrap_or_else(|_| panic!("Unable to write file {doc_md}"));
-    Ok(())
+        None => ParamSet::new(),
+    };
+    for (name, value) in args.param.iter() {
+        params.insert(name, *value);
+    }
+    ElabOpt {
+        lock,
+        strategy: args.alloc_strategy,
+        params,
+        features: args.feature.clone(),
+        variant: args.variant.clone(),
+        reserve_excluded: args.reserve_excluded,
+    }
 }
 
-/// Parse user ClI
-/// Generate is done in two-fold:
+/// Check that the lock file matches the elaborated regmap
+/// Lock file is rewritten with `update`, except with `--locked` where any difference is an error
+fn sync_lock(
+    args: &RegmapArgs,
+    elab: &ElabOpt,
+    regmap: &regmap::Regmap,
+    update: bool,
+    format: Format,
+) {
+    let Some(file) = args.elab.lock_file.as_ref() else {
+        return;
+    };
+    let lock = RegmapLock::from_regmap(regmap);
+    let (added, removed) = elab.lock.clone().unwrap_or_default().changes(&lock);
+    if added.is_empty() && removed.is_empty() && elab.lock.is_some() {
+        return;
+    }
+    if args.locked {
+        let err = RegmapError::LockOutdated {
+            file: file.clone(),
+            added,
+            removed,
+        };
+        report_and_exit(&err.into(), format);
+    }
+    if update {
+        lock.write(file)
+            .unwrap_or_else(|err| report_and_exit(&err.into(), format));
+    }
+}
+
+/// Generation is done in two-fold:
 /// 1. Aggregate all the toml in a fused registermap.
 /// > This regmap is generated with the basename as module name.
-/// > Documetations is generated (i.e. Markdown and json)
-/// 2. Each toml regmap are generated individually
-fn main() -> std::io::Result<()> {
-    let args = Args::parse();
-    println!("User Options: {args:?}");
-
-    // Create a new Tera instances
-    // Analyse all available SystemVerilog template
-    let mut tera_sv = Tera::new("templates/**/*.sv").unwrap();
-    tera_sv.register_function("as_sv_hex", as_sv_hex);
-    // Analyse all available doc template
-    let mut tera_doc = Tera::new("templates/**/fmt_as.*").unwrap();
-    tera_doc.register_function("as_hex", as_hex);
-
-    // Ensure that output folder exist
-    std::fs::create_dir_all(&args.output_path).unwrap();
+/// > Fused generators are applied on it (e.g. documentation)
+/// 2. Each toml regmap are elaborated individually and slice generators are applied on them
+/// > (e.g. SystemVerilog module and package)
+fn generate(args: &GenerateArgs, format: Format, verbose: bool) -> anyhow::Result<()> {
+    // Reject unknown generators before any elaboration
+    let registry = GeneratorRegistry::default();
+    if let Err(err) = registry.select(&args.emit) {
+        eprintln!("Error: {err}");
+        std::process::exit(exit_code::USAGE);
+    }
 
-    // 1. Generate the fused regmap ================================================================
+    // 1. Elaborate the fused regmap ===============================================================
     // Expand regmap => Check properties and expand optional fields
-    // Parse toml files
-    let mut regmap_list = args
-        .toml_file
-        .iter()
-        .map(|toml| regmap::parser::RegmapOpt::read_from(toml))
-        .collect::<Vec<_>>();
-
-    let mut fused_regmap = regmap::Regmap::from_opt(&mut regmap_list).unwrap();
-    if args.verbose {
+    // NB: All errors are gathered and reported at once
+    let elab = elab_opt(&args.regmap.elab, format);
+    let mut fused_regmap = load_regmap(&args.regmap.toml_file, &elab, format);
+    if verbose {
         println!("{fused_regmap}");
     }
+    // Freeze the addresses of the new elements
+    sync_lock(&args.regmap, &elab, &fused_regmap, true, format);
     // Override module_name with basename for the fused version
     // Fused version only used to check that there is no overlapping between file and for documentation
-    // -> No SystemVerilog generated
     *fused_regmap.module_name_mut() = args.basename.clone();
-    generate_doc(&fused_regmap, &args.output_path, &tera_doc)?;
 
-    // 2. Generate individual regmap ===============================================================
-    args.toml_file.iter().for_each(|toml| {
-        let regmap_opt = regmap::parser::RegmapOpt::read_from(toml);
-        let regmap = regmap::Regmap::from_opt(&mut [regmap_opt]).unwrap();
-        generate_sv(&regmap, &args.output_path, &tera_sv);
-    });
+    // 2. Elaborate individual regmap ==============================================================
+    // NB: Errors are reported as for the fused regmap (i.e. all at once, same exit code)
+    let slices = args
+        .regmap
+        .toml_file
+        .iter()
+        .map(|toml| load_regmap(std::slice::from_ref(toml), &elab, format))
+        .collect::<Vec<_>>();
+
+    // Render the selected outputs ==================================================================
+    // Create a new Tera instance with all the available templates
+    // NB: Built-in templates are embedded in the binary, user ones are overlaid on top
+    let tera = generator::tera_engine(args.template_dir.as_deref().map(Path::new))?;
+    let ctx = GenCtx {
+        tera: &tera,
+        tool_version: option_env!("GIT_VERSION").unwrap_or("unknown"),
+        output_path: Path::new(&args.output_path),
+    };
+    let written = registry.run(&args.emit, &fused_regmap, &slices, &ctx)?;
+
+    match format {
+        Format::Human => {
+            if verbose {
+                written
+                    .iter()
+                    .for_each(|f| println!("Generated {}", f.display()));
+            }
+        }
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "status": "ok",
+                "output_path": args.output_path,
+                "module_name": fused_regmap.module_name(),
+                "files": written,
+            })
+        ),
+    }
+    Ok(())
+}
+
+/// Check the register map definition without generating anything
+fn check(args: &RegmapArgs, format: Format) {
+    let elab = elab_opt(&args.elab, format);
+    let regmap = load_regmap(&args.toml_file, &elab, format);
+    sync_lock(args, &elab, &regmap, false, format);
+    // NB: Sections of child blocks are included
+    let sections = regmap.all_sections();
+    let reg_nb = sections
+        .iter()
+        .map(|(_, s)| s.register().len())
+        .sum::<usize>();
+    match format {
+        Format::Human => println!(
+            "Register map is valid: {} sections, {reg_nb} registers, range 0x{:x} @0x{:x}",
+            sections.len(),
+            regmap.range(),
+            regmap.offset()
+        ),
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "status": "ok",
+                "module_name": regmap.module_name(),
+                "offset": regmap.offset(),
+                "range": regmap.range(),
+                "section_nb": sections.len(),
+                "register_nb": reg_nb,
+            })
+        ),
+    }
+}
+
+/// Print address and fields of the matching registers
+fn query(args: &QueryArgs, format: Format) -> i32 {
+    let elab = elab_opt(&args.regmap.elab, format);
+    let flat = FlatRegmap::new(load_regmap(&args.regmap.toml_file, &elab, format));
+
+    // Match either an exact register or all the registers of a section/block
+    let prefix = format!("{}::", args.name);
+    let mut matches = flat
+        .register()
+        .iter()
+        .filter(|(name, _)| **name == args.name || name.starts_with(&prefix))
+        .map(|(_, reg)| reg)
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|reg| *reg.offset());
+
+    if matches.is_empty() {
+        let err = RegmapError::UnknownRegister {
+            name: args.name.clone(),
+        };
+        match format {
+            Format::Human => eprintln!("Error: {err}"),
+            Format::Json => println!("{}", Diagnostics::from(err).to_json()),
+        }
+        return exit_code::NOT_FOUND;
+    }
+
+    match format {
+        Format::Human => matches.iter().for_each(|reg| println!("{reg}")),
+        Format::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&matches)
+                .expect("FlatRegister serialization can not fail")
+        ),
+    }
+    exit_code::SUCCESS
+}
+
+/// Rewrite toml files in the canonical style
+/// With `check`, only report the files that would be rewritten
+fn fmt(args: &FmtArgs, format: Format) -> i32 {
+    let mut diag = Diagnostics::new();
+    let mut changed = Vec::new();
+    for file in args.toml_file.iter() {
+        let content = match std::fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(err) => {
+                diag.push(RegmapError::Io {
+                    file: file.clone(),
+                    msg: err.to_string(),
+                });
+                continue;
+            }
+        };
+        match regmap::format::canonical(file, &content) {
+            Ok(formatted) if formatted != content => {
+                if !args.check {
+                    if let Err(err) = std::fs::write(file, &formatted) {
+                        diag.push(RegmapError::Io {
+                            file: file.clone(),
+                            msg: err.to_string(),
+                        });
+                        continue;
+                    }
+                }
+                changed.push(file.clone());
+            }
+            Ok(_) => {}
+            Err(err) => diag.push(err),
+        }
+    }
+    if !diag.is_empty() {
+        report_and_exit(&diag, format);
+    }
+
+    match format {
+        Format::Human => changed.iter().for_each(|f| {
+            println!(
+                "{} {f}",
+                if args.check {
+                    "Unformatted"
+                } else {
+                    "Formatted"
+                }
+            )
+        }),
+        Format::Json => println!(
+            "{}",
+            serde_json::json!({
+                "status": if args.check && !changed.is_empty() { "unformatted" } else { "ok" },
+                "files": changed,
+            })
+        ),
+    }
+    if args.check && !changed.is_empty() {
+        exit_code::UNFORMATTED
+    } else {
+        exit_code::SUCCESS
+    }
+}
+
+/// Compare two register maps
+/// Exit with a dedicated code on ABI-breaking changes for CI gating
+fn diff(args: &DiffArgs, format: Format) -> i32 {
+    let elab = elab_opt(&args.elab, format);
+    let old = load_regmap(&args.old, &elab, format);
+    let new = load_regmap(&args.new, &elab, format);
+    let diff = RegmapDiff::new(&old, &new);
+    match format {
+        Format::Human => println!("{diff}"),
+        Format::Json => println!("{}", diff.to_json()),
+    }
+    if diff.is_breaking() {
+        exit_code::BREAKING_CHANGE
+    } else {
+        exit_code::SUCCESS
+    }
+}
 
+/// Dump or list the built-in templates
+fn templates(args: &TemplatesArgs, format: Format) -> anyhow::Result<()> {
+    let files = if args.list {
+        templates::BUILTIN_TEMPLATES
+            .iter()
+            .map(|(name, _)| PathBuf::from(name))
+            .collect()
+    } else {
+        templates::dump(Path::new(&args.output_path), args.force)?
+    };
+    match format {
+        Format::Human => files.iter().for_each(|f| println!("{}", f.display())),
+        Format::Json => println!("{}", serde_json::json!({ "status": "ok", "files": files })),
+    }
     Ok(())
 }
+
+/// Print or write the JSON Schema of the toml definitions
+fn schema(args: &SchemaArgs, format: Format) -> anyhow::Result<()> {
+    let schema = if args.fragment {
+        regmap::parser::FragmentOpt::json_schema()
+    } else {
+        regmap::parser::RegmapOpt::json_schema()
+    };
+    let Some(file) = args.output_path.as_ref() else {
+        println!("{schema}");
+        return Ok(());
+    };
+    std::fs::write(file, schema + "\n")?;
+    if format == Format::Json {
+        println!("{}", serde_json::json!({ "status": "ok", "files": [file] }));
+    }
+    Ok(())
+}
+
+/// Parse user ClI and dispatch on the requested subcommand
+fn main() {
+    let args = Args::parse();
+    if args.verbose {
+        println!("User Options: {args:?}");
+    }
+
+    let code = match &args.cmd {
+        Command::Generate(gen_args) => match generate(gen_args, args.format, args.verbose) {
+            Ok(()) => exit_code::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {err:?}");
+                exit_code::GENERATION
+            }
+        },
+        Command::Check(regmap_args) => {
+            check(regmap_args, args.format);
+            exit_code::SUCCESS
+        }
+        Command::Query(query_args) => query(query_args, args.format),
+        Command::Fmt(fmt_args) => fmt(fmt_args, args.format),
+        Command::Diff(diff_args) => diff(diff_args, args.format),
+        Command::Templates(tpl_args) => match templates(tpl_args, args.format) {
+            Ok(()) => exit_code::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {err}");
+                exit_code::GENERATION
+            }
+        },
+        Command::Schema(schema_args) => match schema(schema_args, args.format) {
+            Ok(()) => exit_code::SUCCESS,
+            Err(err) => {
+                eprintln!("Error: {err}");
+                exit_code::GENERATION
+            }
+        },
+    };
+    std::process::exit(code);
+}
@@ -0,0 +1,182 @@
+// ============================================================================================== //
+// Description  : Axi4-lite address decoder
+// This file was generated with rust regmap generator:
+//  * Date:  {{ now() | date(format="%Y-%m-%d") }}
+//  * Tool_version: {{ tool_version }}
+// ---------------------------------------------------------------------------------------------- //
+// Route each request of the slave interface to the target that owns its address.
+// Targets windows are naturally aligned (i.e. offset is a multiple of the power-of-two range),
+// thus the decoding is done with masks. Relocatable targets see the address relative to their
+// window, the others see the full address.
+{%- if default_target %}
+// Requests outside of all the windows are routed to `{{ default_target }}`.
+{%- else %}
+// Requests outside of all the windows are answered with SLVERR.
+{%- endif %}
+//
+// Only one write and one read are in flight at a time: the selected target is frozen from the
+// address handshake until the response handshake.
+// ============================================================================================== //
+
+module {{module_name}}
+{%for pkg in ext_pkg%}
+import {{pkg}}::*;
+{%endfor%}
+(
+  input  logic                           clk,
+  input  logic                           s_rst_n,
+
+  // Axi4 lite Slave Interface sAxi4
+  {%- for sig in axil %}
+  {% if sig.request %}input {% else %}output{% endif %} {{sig.ty}} s_axil_{{sig.name}},
+  {%- endfor %}
+
+  {%- for tgt in targets %}
+  // Axi4 lite Master Interface {{tgt.name}}
+  {%- set last_tgt = loop.last %}
+  {%- for sig in axil %}
+  {% if sig.request %}output{% else %}input {% endif %} {{sig.ty}} m_{{tgt.name}}_axil_{{sig.name}}{% if not last_tgt or not loop.last %},{% endif %}
+  {%- endfor %}
+  {%- endfor %}
+);
+
+// ============================================================================================== --
+// localparam
+// ============================================================================================== --
+  localparam int TGT_NB = {{ targets | length }};
+  {%- for tgt in targets %}{% if tgt.window %}
+  localparam [AXIL_ADD_W-1:0] {{tgt.name | upper}}_OFS  = AXIL_ADD_W'({{as_sv_hex(val=tgt.offset)}});
+  localparam [AXIL_ADD_W-1:0] {{tgt.name | upper}}_MASK = ~(AXIL_ADD_W'({{as_sv_hex(val=tgt.range)}} - 1));
+  {%- endif %}{% endfor %}
+
+// ============================================================================================== --
+// Address decoding
+// ============================================================================================== --
+  logic [TGT_NB-1:0] wr_dec;
+  logic [TGT_NB-1:0] rd_dec;
+  logic              wr_miss;
+  logic              rd_miss;
+
+  {%- for tgt in targets %}{% if tgt.window %}
+  assign wr_dec[{{loop.index0}}] = (s_axil_awaddr & {{tgt.name | upper}}_MASK) == {{tgt.name | upper}}_OFS;
+  assign rd_dec[{{loop.index0}}] = (s_axil_araddr & {{tgt.name | upper}}_MASK) == {{tgt.name | upper}}_OFS;
+  {%- endif %}{% endfor %}
+  {%- for tgt in targets %}{% if not tgt.window %}
+  // Default target: everything outside the other windows
+  assign wr_dec[{{loop.index0}}] = ~|(wr_dec & TGT_NB'({{ window_mask }}));
+  assign rd_dec[{{loop.index0}}] = ~|(rd_dec & TGT_NB'({{ window_mask }}));
+  {%- endif %}{% endfor %}
+  assign wr_miss = ~|wr_dec;
+  assign rd_miss = ~|rd_dec;
+
+// ============================================================================================== --
+// Write path
+// ============================================================================================== --
+  // Handshakes already done for the current write
+  logic              aw_done;
+  logic              w_done;
+  logic [TGT_NB-1:0] wr_sel_q;
+  logic              wr_miss_q;
+  logic [TGT_NB-1:0] wr_sel;
+  logic              wr_miss_sel;
+
+  // NB: Address must be kept stable until awready, thus decoding is valid until the handshake
+  assign wr_sel      = aw_done ? wr_sel_q  : wr_dec;
+  assign wr_miss_sel = aw_done ? wr_miss_q : wr_miss;
+
+  {%- for tgt in targets %}
+  assign m_{{tgt.name}}_axil_awaddr  = s_axil_awaddr{% if tgt.relative %} & ~{{tgt.name | upper}}_MASK{% endif %};
+  assign m_{{tgt.name}}_axil_awvalid = s_axil_awvalid & ~aw_done & wr_sel[{{loop.index0}}];
+  assign m_{{tgt.name}}_axil_wdata   = s_axil_wdata;
+  assign m_{{tgt.name}}_axil_wvalid  = s_axil_wvalid & ~w_done & (s_axil_awvalid | aw_done) & wr_sel[{{loop.index0}}];
+  assign m_{{tgt.name}}_axil_bready  = s_axil_bready & aw_done & w_done & wr_sel_q[{{loop.index0}}];
+  {%- endfor %}
+
+  assign s_axil_awready = ~aw_done & (wr_miss_sel
+                          {%- for tgt in targets %}
+                          | (wr_sel[{{loop.index0}}] & m_{{tgt.name}}_axil_awready)
+                          {%- endfor %});
+  assign s_axil_wready  = ~w_done & (s_axil_awvalid | aw_done) & (wr_miss_sel
+                          {%- for tgt in targets %}
+                          | (wr_sel[{{loop.index0}}] & m_{{tgt.name}}_axil_wready)
+                          {%- endfor %});
+  assign s_axil_bvalid  = aw_done & w_done & (wr_miss_q
+                          {%- for tgt in targets %}
+                          | (wr_sel_q[{{loop.index0}}] & m_{{tgt.name}}_axil_bvalid)
+                          {%- endfor %});
+  assign s_axil_bresp   = wr_miss_q ? AXI4_SLVERR : ('0
+                          {%- for tgt in targets %}
+                          | ({AXI4_RESP_W{wr_sel_q[{{loop.index0}}]}} & m_{{tgt.name}}_axil_bresp)
+                          {%- endfor %});
+
+  always_ff @(posedge clk) begin
+    if (!s_rst_n) begin
+      aw_done   <= 1'b0;
+      w_done    <= 1'b0;
+      wr_sel_q  <= '0;
+      wr_miss_q <= 1'b0;
+    end
+    else if (s_axil_bvalid && s_axil_bready) begin
+      aw_done   <= 1'b0;
+      w_done    <= 1'b0;
+    end
+    else begin
+      if (s_axil_awvalid && s_axil_awready) begin
+        aw_done   <= 1'b1;
+        wr_sel_q  <= wr_dec;
+        wr_miss_q <= wr_miss;
+      end
+      if (s_axil_wvalid && s_axil_wready) begin
+        w_done    <= 1'b1;
+      end
+    end
+  end
+
+// ============================================================================================== --
+// Read path
+// ============================================================================================== --
+  // Address handshake already done for the current read
+  logic              ar_done;
+  logic [TGT_NB-1:0] rd_sel_q;
+  logic              rd_miss_q;
+
+  {%- for tgt in targets %}
+  assign m_{{tgt.name}}_axil_araddr  = s_axil_araddr{% if tgt.relative %} & ~{{tgt.name | upper}}_MASK{% endif %};
+  assign m_{{tgt.name}}_axil_arvalid = s_axil_arvalid & ~ar_done & rd_dec[{{loop.index0}}];
+  assign m_{{tgt.name}}_axil_rready  = s_axil_rready & ar_done & rd_sel_q[{{loop.index0}}];
+  {%- endfor %}
+
+  assign s_axil_arready = ~ar_done & (rd_miss
+                          {%- for tgt in targets %}
+                          | (rd_dec[{{loop.index0}}] & m_{{tgt.name}}_axil_arready)
+                          {%- endfor %});
+  assign s_axil_rvalid  = ar_done & (rd_miss_q
+                          {%- for tgt in targets %}
+                          | (rd_sel_q[{{loop.index0}}] & m_{{tgt.name}}_axil_rvalid)
+                          {%- endfor %});
+  assign s_axil_rresp   = rd_miss_q ? AXI4_SLVERR : ('0
+                          {%- for tgt in targets %}
+                          | ({AXI4_RESP_W{rd_sel_q[{{loop.index0}}]}} & m_{{tgt.name}}_axil_rresp)
+                          {%- endfor %});
+  assign s_axil_rdata   = rd_miss_q ? AXIL_DATA_W'('hDEAD_ADD3) : ('0
+                          {%- for tgt in targets %}
+                          | ({AXIL_DATA_W{rd_sel_q[{{loop.index0}}]}} & m_{{tgt.name}}_axil_rdata)
+                          {%- endfor %});
+
+  always_ff @(posedge clk) begin
+    if (!s_rst_n) begin
+      ar_done   <= 1'b0;
+      rd_sel_q  <= '0;
+      rd_miss_q <= 1'b0;
+    end
+    else if (s_axil_rvalid && s_axil_rready) begin
+      ar_done   <= 1'b0;
+    end
+    else if (s_axil_arvalid && s_axil_arready) begin
+      ar_done   <= 1'b1;
+      rd_sel_q  <= rd_dec;
+      rd_miss_q <= rd_miss;
+    end
+  end
+
+endmodule
@@ -226,6 +226,15 @@ import {{module_name}}_pkg::*;
 // ============================================================================================== --
 {%- for reg in regs_sv -%}{{reg.default_snippets}}{% endfor %}
 
+// ============================================================================================== --
+// Parameter checks
+// ============================================================================================== --
+  // Parameters used as default value must fit in their register/field
+  // Parameters used as field width must be in [1, max width]
+  generate
+  {%- for reg in regs_sv -%}{{reg.check_snippets}}{% endfor %}
+  endgenerate
+
 // ============================================================================================== --
 // Write reg
 // ============================================================================================== --
@@ -253,8 +262,20 @@ import {{module_name}}_pkg::*;
           axil_rrespD = AXI4_OKAY;
           case(rd_add[AXIL_ADD_RANGE_W-1:0])
           {%- for reg in regs_sv -%}{{reg.rd_snippets}}{% endfor %}
+          {%- if rsvd_ranges %}
+          default: begin
+            axil_rdataD = REG_DATA_W'('h0BAD_ADD1); // Default value
+            {%- for rsvd in rsvd_ranges %}
+            // Reserved range {{rsvd.name}} ({{rsvd.policy}}) is read as 0
+            if (((rd_add & AXIL_ADD_RANGE_MASK) >= (AXIL_ADD_W'({{as_sv_hex(val=rsvd.offset)}}) & AXIL_ADD_RANGE_MASK))
+                && ((rd_add & AXIL_ADD_RANGE_MASK) <= (AXIL_ADD_W'({{as_sv_hex(val=rsvd.last)}}) & AXIL_ADD_RANGE_MASK)))
+              axil_rdataD = '0;
+            {%- endfor %}
+          end
+          {%- else %}
           default:
             axil_rdataD = REG_DATA_W'('h0BAD_ADD1); // Default value
+          {%- endif %}
           endcase // rd_add
         end
       end // if rd_end
@@ -0,0 +1,14 @@
+{# Template for generating parameter width checks #}
+{# Warn: Keep indentation in phase with module template (cf. check_snippets) #}
+{% for chk in param_checks %}
+  // Check that parameter {{chk.param}} fits in {{chk.target}} [{{chk.width}} bits]
+  if (({{chk.param}} < 0) || ({{chk.param}} > {{as_sv_hex(val=chk.max)}})) begin : check_{{chk.label}}
+    $error("Parameter {{chk.param}}=%0d doesn't fit in {{chk.target}} [{{chk.width}} bits]", {{chk.param}});
+  end
+{% endfor %}
+{% for wp in width_params %}
+  // Check that parameter {{wp.param}} is a valid width for {{wp.target}} [max {{wp.max}} bits]
+  if (({{wp.param}} < 1) || ({{wp.param}} > {{wp.max}})) begin : check_{{wp.label}}_width
+    $error("Parameter {{wp.param}}=%0d isn't a valid width for {{wp.target}} [max {{wp.max}} bits]", {{wp.param}});
+  end
+{% endfor %}
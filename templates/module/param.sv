@@ -1,6 +1,6 @@
 {# Template for generating RTL parameter #}
 {# Warn: Keep indentation in phase with module template (cf. param_snippets) #}
 {% for d in default_name %}
-    parameter int {{ d }} = 0
+    parameter int {{ d }} = {% if d in param_value %}{{ param_value[d] }}{% else %}0{% endif %}
     {%- if loop.last %}{% break %}{% endif -%},
 {% endfor %}
@@ -1,8 +1,11 @@
 {# Template for generating subcase part for read section #}
 {# Warn: Keep indentation in phase with module template (cf. rd_snippets) #}
-          {{ offset_cst_name }}[AXIL_ADD_RANGE_W-1:0]: begin // register {{ name }}
+          {{ offset_cst_name }}[AXIL_ADD_RANGE_W-1:0]: begin // register {{ name }}{% if reserved %} (reserved {{ reserved }}){% endif %}
             {% if param_reg %}
             axil_rdataD = {{name}}_default;
+            {% elif raz_mask %}
+            // Read-as-zero bits
+            axil_rdataD = r_{{name}} & ~{{raz_name}};
             {% else %}
             axil_rdataD = r_{{name}};
             {% endif %}
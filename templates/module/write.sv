@@ -1,18 +1,47 @@
 {# Template for generating storage and update logic #}
 {# Warn: Keep indentation in phase with module template (cf. ff_wr_snippets) #}
 {%- if not param_reg -%}
+  {%- if width_params -%}
+  {%- set wr_data = "(wr_data & " ~ mask_name ~ ")" -%}
+  {%- else -%}
+  {%- set wr_data = "wr_data" -%}
+  {%- endif -%}
+  {%- if wi_mask -%}
+  {%- set wr_data = "(" ~ wr_data ~ " & ~" ~ rsvd_name ~ " | r_" ~ name ~ " & " ~ wi_name ~ ")" -%}
+  {%- elif rsvd_mask -%}
+  {%- set wr_data = "(" ~ wr_data ~ " & ~" ~ rsvd_name ~ ")" -%}
+  {%- endif -%}
   // Register FF: {{name}}
+  {% if reserved %}
+  // Reserved ({{reserved}}), stored without port
+  logic [REG_DATA_W-1:0] r_{{name}};
+  {% endif %}
   logic [REG_DATA_W-1:0] r_{{name}}D;
+  {% if width_params %}
+  // Bits above the parameterized field widths are written as 0
+  localparam logic [REG_DATA_W-1:0] {{mask_name}} = ~({% for wp in width_params %}
+    (((REG_DATA_W'(1) << {{wp.max}}) - (REG_DATA_W'(1) << {{wp.param}})) << {{wp.offset}}){% if not loop.last %} |{% endif %}{% endfor %});
+  {% endif %}
+  {% if rsvd_mask %}
+  // Reserved bits aren't written: read-as-zero ones stay 0, write-ignored ones keep their value
+  localparam logic [REG_DATA_W-1:0] {{rsvd_name}} = {{as_sv_hex(val=rsvd_mask)}};
+  {% if raz_mask %}
+  localparam logic [REG_DATA_W-1:0] {{raz_name}} = {{as_sv_hex(val=raz_mask)}};
+  {% endif %}
+  {% if wi_mask %}
+  localparam logic [REG_DATA_W-1:0] {{wi_name}} = {{as_sv_hex(val=wi_mask)}};
+  {% endif %}
+  {% endif %}
 
   {%  if reg_update %}
     {%  if wr_user %}
-  assign r_{{name}}D = (wr_en_ok && (wr_add[AXIL_ADD_RANGE_W-1:0] == {{offset_cst_name}}[AXIL_ADD_RANGE_W-1:0]))? wr_data: r_{{name}}_upd;
+  assign r_{{name}}D = (wr_en_ok && (wr_add[AXIL_ADD_RANGE_W-1:0] == {{offset_cst_name}}[AXIL_ADD_RANGE_W-1:0]))? {{wr_data}}: r_{{name}}_upd;
     {% else %}
   assign r_{{name}}D       = r_{{name}}_upd;
     {% endif %}
   {% else %}
     {%  if wr_user %}
-  assign r_{{name}}D = (wr_en_ok && (wr_add[AXIL_ADD_RANGE_W-1:0] == {{offset_cst_name}}[AXIL_ADD_RANGE_W-1:0]))? wr_data: r_{{name}};
+  assign r_{{name}}D = (wr_en_ok && (wr_add[AXIL_ADD_RANGE_W-1:0] == {{offset_cst_name}}[AXIL_ADD_RANGE_W-1:0]))? {{wr_data}}: r_{{name}};
     {% endif %}
   {% endif %}
 
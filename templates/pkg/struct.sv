@@ -3,6 +3,6 @@
 
   typedef struct packed {
     {% for nos in fields_nos %}
-    logic [({{nos.2}}-1):0] {{nos.0}};
+    logic [({{nos.2}}-1):0] {{nos.0}};{% if nos.3 %} // Width {{nos.3}} (max {{nos.2}}), upper bits are 0, slice with [{{nos.3}}-1:0]{% elif nos.4 %} // Reserved ({{nos.4}}){% endif %}
     {% endfor %}
    } {{base_name}}_t;
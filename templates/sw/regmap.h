@@ -0,0 +1,40 @@
+// ============================================================================================== //
+// Description  : {{module_name}} register map definition
+// This file was generated with rust regmap generator:
+//  * Date:  {{ now() | date(format="%Y-%m-%d") }}
+//  * Tool_version: {{ tool_version }}
+// ============================================================================================== //
+
+#ifndef {{module_name | upper}}_REGMAP_H
+#define {{module_name | upper}}_REGMAP_H
+
+#define {{module_name | upper}}_OFS   {{as_hex(val=offset)}}
+#define {{module_name | upper}}_RANGE {{as_hex(val=range)}}
+{% for rsvd in rsvd_ranges %}
+// Reserved ({{rsvd.reserved}}): {{rsvd.description}}
+#define {{rsvd.name}}_RSVD_OFS   {{as_hex(val=rsvd.offset)}}
+#define {{rsvd.name}}_RSVD_RANGE {{as_hex(val=rsvd.range)}}
+{% endfor %}
+{%- for reg in regs %}
+// {% if reg.reserved %}Reserved ({{reg.reserved}}): {% endif %}{{reg.description}}
+#define {{reg.name}}_OFS {{as_hex(val=reg.offset)}}
+{%- if reg.reset is number %}
+#define {{reg.name}}_RESET {{as_hex(val=reg.reset)}}
+{%- endif %}
+{%- if reg.rsvd_mask %}
+// Reserved bits, must be written back as read in {{reg.name}}_PRESERVE_MASK
+#define {{reg.name}}_RSVD_MASK {{as_hex(val=reg.rsvd_mask)}}
+#define {{reg.name}}_PRESERVE_MASK {{as_hex(val=reg.preserve_mask)}}
+{%- endif %}
+{%- for f in reg.field %}
+{%- if f.reserved %}
+// Reserved ({{f.reserved}}): {{f.description}} [bits {{f.shift}}..{{f.shift + f.size_b}}]
+{%- else %}
+// {{f.description}}
+#define {{f.name}}_SHIFT {{f.shift}}
+#define {{f.name}}_WIDTH {{f.size_b}}
+#define {{f.name}}_MASK  {{as_hex(val=f.mask)}}
+{%- endif %}
+{%- endfor %}
+{% endfor %}
+#endif // {{module_name | upper}}_REGMAP_H
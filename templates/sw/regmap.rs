@@ -0,0 +1,36 @@
+// ============================================================================================== //
+// Description  : {{module_name}} register map definition
+// This file was generated with rust regmap generator:
+//  * Date:  {{ now() | date(format="%Y-%m-%d") }}
+//  * Tool_version: {{ tool_version }}
+// ============================================================================================== //
+
+pub const {{module_name | upper}}_OFS: usize = {{as_hex(val=offset)}};
+pub const {{module_name | upper}}_RANGE: usize = {{as_hex(val=range)}};
+{% for rsvd in rsvd_ranges %}
+/// Reserved ({{rsvd.reserved}}): {{rsvd.description}}
+pub const {{rsvd.name}}_RSVD_OFS: usize = {{as_hex(val=rsvd.offset)}};
+pub const {{rsvd.name}}_RSVD_RANGE: usize = {{as_hex(val=rsvd.range)}};
+{% endfor %}
+{%- for reg in regs %}
+/// {% if reg.reserved %}Reserved ({{reg.reserved}}): {% endif %}{{reg.description}}
+pub const {{reg.name}}_OFS: usize = {{as_hex(val=reg.offset)}};
+{%- if reg.reset is number %}
+pub const {{reg.name}}_RESET: u{{word_size_b}} = {{as_hex(val=reg.reset)}};
+{%- endif %}
+{%- if reg.rsvd_mask %}
+/// Reserved bits, must be written back as read in {{reg.name}}_PRESERVE_MASK
+pub const {{reg.name}}_RSVD_MASK: u{{word_size_b}} = {{as_hex(val=reg.rsvd_mask)}};
+pub const {{reg.name}}_PRESERVE_MASK: u{{word_size_b}} = {{as_hex(val=reg.preserve_mask)}};
+{%- endif %}
+{%- for f in reg.field %}
+{%- if f.reserved %}
+// Reserved ({{f.reserved}}): {{f.description}} [bits {{f.shift}}..{{f.shift + f.size_b}}]
+{%- else %}
+/// {{f.description}}
+pub const {{f.name}}_SHIFT: u32 = {{f.shift}};
+pub const {{f.name}}_WIDTH: u32 = {{f.size_b}};
+pub const {{f.name}}_MASK: u{{word_size_b}} = {{as_hex(val=f.mask)}};
+{%- endif %}
+{%- endfor %}
+{% endfor %}
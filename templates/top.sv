@@ -0,0 +1,80 @@
+// ============================================================================================== //
+// Description  : Axi4-lite {% if default_target %}hierarchical register bank{% else %}interconnect{% endif %}
+// This file was generated with rust regmap generator:
+//  * Date:  {{ now() | date(format="%Y-%m-%d") }}
+//  * Tool_version: {{ tool_version }}
+// ---------------------------------------------------------------------------------------------- //
+{%- if default_target %}
+// Register bank `{{ regmap_name }}` with its child blocks.
+// Requests are routed by an address decoder toward the child blocks, all the other addresses are
+// handled by the local registers.
+//
+// Ports of the child blocks are prefixed by the block instance name.
+{%- else %}
+// Register map `{{ regmap_name }}` split in several slices.
+// Requests are routed by an address decoder toward the slice modules, unmapped addresses are
+// answered with SLVERR.
+//
+// Ports of the slices are prefixed by the slice module name.
+{%- endif %}
+// NB: Parameters of the instantiated modules keep their default value.
+// ============================================================================================== //
+
+module {{module_name}}
+{%for pkg in ext_pkg%}
+import {{pkg}}::*;
+{%endfor%}
+(
+  input  logic                           clk,
+  input  logic                           s_rst_n,
+
+  // Axi4 lite Slave Interface sAxi4
+  {%- for sig in axil %}
+  {% if sig.request %}input {% else %}output{% endif %} {{sig.ty}} s_axil_{{sig.name}}{% if not loop.last %},{% endif %}
+  {%- endfor %}
+  {%- for port in ports %}
+  , {{port.dir}} {{port.ty}} {{port.name}}
+  {%- endfor %}
+);
+
+// ============================================================================================== --
+// Address decoder
+// ============================================================================================== --
+  {%- for tgt in targets %}
+  {%- for sig in axil %}
+  {{sig.ty}} {{tgt.name}}_axil_{{sig.name}};
+  {%- endfor %}
+  {%- endfor %}
+
+  {{ decoder_name }} u_decoder (
+    .clk            (clk),
+    .s_rst_n        (s_rst_n),
+    {%- for sig in axil %}
+    .s_axil_{{sig.name}} (s_axil_{{sig.name}}),
+    {%- endfor %}
+    {%- for tgt in targets %}
+    {%- set last_tgt = loop.last %}
+    {%- for sig in axil %}
+    .m_{{tgt.name}}_axil_{{sig.name}} ({{tgt.name}}_axil_{{sig.name}}){% if not last_tgt or not loop.last %},{% endif %}
+    {%- endfor %}
+    {%- endfor %}
+  );
+
+// ============================================================================================== --
+// Instances
+// ============================================================================================== --
+  {%- for inst in instances %}
+  // {{ inst.description }}
+  {{ inst.module }} u_{{ inst.name }} (
+    .clk            (clk),
+    .s_rst_n        (s_rst_n),
+    {%- for sig in axil %}
+    .s_axil_{{sig.name}} ({{inst.target}}_axil_{{sig.name}}){% if not loop.last or inst.ports %},{% endif %}
+    {%- endfor %}
+    {%- for port in inst.ports %}
+    .{{port.name}} ({{inst.prefix}}{{port.name}}){% if not loop.last %},{% endif %}
+    {%- endfor %}
+  );
+  {%- endfor %}
+
+endmodule